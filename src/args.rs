@@ -1,15 +1,27 @@
-use memmap2::MmapOptions;
+use chrono::{DateTime, FixedOffset};
 use std::{
     env::current_exe,
     ffi::OsString,
     fmt::Display,
     fs::{File, OpenOptions},
     io::IsTerminal,
-    os::unix::ffi::OsStrExt,
+    os::{
+        fd::AsFd,
+        unix::{ffi::OsStrExt, fs::FileTypeExt},
+    },
     path::{Path, PathBuf},
 };
 
-use crate::{utils::TrimAsciiWhitespace, Result};
+use crate::{
+    asn::parse_asn,
+    error::{Error, WithPath},
+    hook::{self, Hook},
+    ip::Overlap,
+    log::{self, parse_since, Rotation, Target},
+    tools,
+    utils::{Input, Split, TrimAsciiWhitespace},
+    Result,
+};
 
 pub enum Webhook {
     Discord(OsString),
@@ -36,16 +48,102 @@ impl Display for Webhook {
     }
 }
 
+/// First arguments that run a command instead of inserting assets.
+pub const COMMANDS: [&str; 14] = [
+    "domain",
+    "ip",
+    "tree",
+    "words",
+    "export",
+    "diff",
+    "snapshot",
+    "snapshots",
+    "restore",
+    "undo",
+    "log",
+    "migrate",
+    "programs",
+    "watch",
+];
+
 pub struct Args {
     pub quiet: bool,
     pub notification: bool,
     pub piped: bool,
     pub verbosity: bool,
+    pub format: Option<String>,
+    pub yes: bool,
+    pub json: bool,
+    pub since: Option<DateTime<FixedOffset>>,
+    /// Logs written by `insert`
+    pub log: Target,
+    /// `[program.]on_new_<kind> = command` lines of the config
+    pub hooks: Vec<Hook>,
+    pub hook_timeout: std::time::Duration,
+    pub filter: Filter,
+    pub insert: Insert,
+    pub words: Words,
+    pub diff: Diff,
+    pub watch: Watch,
     pub program: Option<OsString>,
     pub args: Vec<OsString>,
     pub path: PathBuf,
     pub webhooks: Vec<Webhook>,
 }
+
+/// Filters of `domain` and `ip` searches, `-m` and `--exclude-cdn` apply to `export` too.
+#[derive(Default)]
+pub struct Filter {
+    pub overlap: Overlap,
+    pub merged: bool,
+    pub asn: Vec<u32>,
+    pub provider: Option<String>,
+    pub exclude_cdn: bool,
+    pub apex: Option<String>,
+}
+
+impl Filter {
+    /// Whether IPs are filtered by their tags.
+    pub fn tagged(&self) -> bool {
+        !self.asn.is_empty() || self.provider.is_some() || self.exclude_cdn
+    }
+}
+
+/// Options of an insert.
+#[derive(Default)]
+pub struct Insert {
+    pub split: Split,
+    pub create: bool,
+    /// Snapshot the program before the write that brings an insert to this many new assets
+    pub snapshot_above: Option<usize>,
+    pub rotation: Rotation,
+    /// How piped lines are read
+    pub format: tools::Format,
+}
+
+/// Options of `tree` and `words`.
+#[derive(Default)]
+pub struct Words {
+    pub depth: Option<usize>,
+    pub top: Option<usize>,
+    pub permutations: bool,
+}
+
+/// Options of `diff`.
+#[derive(Default)]
+pub struct Diff {
+    pub snapshots: Vec<OsString>,
+}
+
+/// Options of `watch`.
+#[derive(Default)]
+pub struct Watch {
+    /// Command fed with the assets it sees
+    pub exec: Option<String>,
+    /// Send the assets it sees to the webhooks
+    pub notify: bool,
+}
+
 impl Args {
     pub fn parse() -> Result<Self> {
         use lexopt::prelude::*;
@@ -54,14 +152,28 @@ impl Args {
         let mut notification = false;
         let mut piped = false;
         let mut verbosity = false;
+        let mut format = None;
+        let mut input_format = false;
+        let mut yes = false;
+        let mut json = false;
+        let mut since = None;
+        let mut log = Target::default();
+        let mut hooks = Vec::new();
+        let mut hook_timeout = hook::TIMEOUT;
+        let mut filter = Filter::default();
+        let mut insert = Insert::default();
+        let mut words = Words::default();
+        let mut diff = Diff::default();
+        let mut watch = Watch::default();
         let mut program = None;
         let mut args = Vec::new();
         let mut path = PathBuf::from(".");
         let mut webhooks = Vec::new();
 
         // Parsing Config File
-        let mmap = unsafe { MmapOptions::new().map(&get_config_file()?.1).unwrap() };
-        for line in mmap.split(|c| *c == b'\n') {
+        let (cfg_path, cfg_file) = get_config_file()?;
+        let cfg = Input::from_file(&cfg_file).with_path(&cfg_path)?;
+        for line in cfg.split(|c| *c == b'\n') {
             let line = line.trim_ascii_whitespace();
             if let Ok(webhook) = Webhook::try_from(line) {
                 webhooks.push(webhook);
@@ -72,6 +184,42 @@ impl Args {
 
                 if Path::new(&line).is_dir() {
                     path = PathBuf::from(&line);
+                } else if let Some((key, value)) = line.to_string_lossy().split_once('=') {
+                    match key.trim() {
+                        "input" => insert.split = value.trim().parse().map_err(Error::Config)?,
+                        "delimiter" => insert.split = Split::Delimiter(value.trim().to_string()),
+                        "input_format" => {
+                            insert.format = value.trim().parse().map_err(Error::Config)?
+                        }
+                        "log" => log = value.trim().parse().map_err(Error::Config)?,
+                        "log_max_size" => {
+                            insert.rotation.max_size =
+                                Some(log::parse_size(value).map_err(Error::Config)?)
+                        }
+                        "log_max_age" => {
+                            insert.rotation.max_age =
+                                Some(log::parse_duration(value).ok_or_else(|| {
+                                    Error::Config(format!(
+                                        "{value:?} is not a duration, use 12h, 7d, 2w"
+                                    ))
+                                })?)
+                        }
+                        "hook_timeout" => {
+                            hook_timeout = log::parse_duration(value)
+                                .and_then(|d| d.to_std().ok())
+                                .ok_or_else(|| {
+                                    Error::Config(format!(
+                                        "{value:?} is not a duration, use 30s, 5m, 1h"
+                                    ))
+                                })?
+                        }
+                        "snapshot_above" => {
+                            insert.snapshot_above = Some(value.trim().parse().map_err(|_| {
+                                Error::Config(format!("{value:?} is not a number of assets"))
+                            })?)
+                        }
+                        key => hooks.extend(Hook::parse(key, value).map_err(Error::Config)?),
+                    }
                 }
             }
         }
@@ -89,6 +237,70 @@ impl Args {
                 Short('v') | Long("verbosity") => {
                     verbosity = true;
                 }
+                Long("overlap") => {
+                    filter.overlap = parser.value()?.parse()?;
+                }
+                Short('m') | Long("merged") => {
+                    filter.merged = true;
+                }
+                Long("asn") => {
+                    filter.asn.push(parser.value()?.parse_with(parse_asn)?);
+                }
+                Long("provider") => {
+                    filter.provider = Some(parser.value()?.string()?.to_lowercase());
+                }
+                Long("exclude-cdn") => {
+                    filter.exclude_cdn = true;
+                }
+                Long("apex") => {
+                    filter.apex = Some(parser.value()?.string()?.to_lowercase());
+                }
+                Short('d') | Long("depth") => {
+                    words.depth = Some(parser.value()?.parse()?);
+                }
+                Short('t') | Long("top") => {
+                    words.top = Some(parser.value()?.parse()?);
+                }
+                Short('P') | Long("permutations") => {
+                    words.permutations = true;
+                }
+                Short('f') | Long("format") => {
+                    format = Some(parser.value()?.string()?);
+                }
+                Long("input-format") => {
+                    insert.format = parser.value()?.parse()?;
+                    input_format = true;
+                }
+                Short('l') | Long("lines") => {
+                    insert.split = Split::Lines;
+                }
+                Long("tokens") => {
+                    insert.split = Split::Tokens;
+                }
+                Short('D') | Long("delimiter") => {
+                    insert.split = Split::Delimiter(parser.value()?.string()?);
+                }
+                Long("create") => {
+                    insert.create = true;
+                }
+                Short('y') | Long("yes") => {
+                    yes = true;
+                }
+                Long("json") => {
+                    json = true;
+                }
+                Long("since") => {
+                    since = Some(parser.value()?.parse_with(parse_since)?);
+                }
+                Long("snapshot") => {
+                    diff.snapshots.push(parser.value()?);
+                }
+                Long("exec") => {
+                    watch.exec = Some(parser.value()?.string()?);
+                }
+                Long("notify") => {
+                    watch.notify = true;
+                }
                 Short('p') | Long("program") => {
                     program = Some(parser.value()?);
                 }
@@ -97,11 +309,10 @@ impl Args {
                     if Path::new(&str).is_dir() {
                         path = PathBuf::from(&str);
                     } else {
-                        return Err(format!(
+                        return Err(Error::Config(format!(
                             "{:?} is not a Directory.\n{:?} will be used!",
                             str, path
-                        )
-                        .into());
+                        )));
                     }
                 }
 
@@ -127,30 +338,79 @@ impl Args {
             }
         }
 
-        // Check if somthing is piped or not
-        if !std::io::stdin().is_terminal() {
-            let mmap = unsafe { MmapOptions::new().map(&std::io::stdin()).unwrap() };
-
-            mmap.split(|c| c.is_ascii_whitespace())
-                .map(|bytes| unsafe { OsString::from_encoded_bytes_unchecked(bytes.to_vec()) })
-                .for_each(|str| args.push(str));
-
+        // Check if somthing is piped or not, it's read later as a stream, by an insert with -p
+        if is_piped() {
             piped = true;
         }
 
-        Ok(Args {
+        let args = Args {
             quiet,
             notification,
             piped,
             verbosity,
+            format,
+            yes,
+            json,
+            since,
+            log,
+            hooks,
+            hook_timeout,
+            filter,
+            insert,
+            words,
+            diff,
+            watch,
             program,
             args,
             path,
             webhooks,
-        })
+        };
+
+        if args.format.is_some() && args.args.first().is_none_or(|first| first != "export") {
+            return Err(Error::Config(
+                "-f/--format is for export, use --input-format to read piped lines".into(),
+            ));
+        }
+        if input_format && !args.inserts() {
+            return Err(Error::Config("--input-format is only for inserts".into()));
+        }
+        Ok(args)
+    }
+
+    /// Whether the arguments are assets to insert rather than a command or nothing.
+    ///
+    /// Without arguments, stdin is only read with -p: scripts and cron jobs
+    /// run `hust` with a non terminal stdin to get the status.
+    pub fn inserts(&self) -> bool {
+        match self.args.first() {
+            Some(first) => !COMMANDS.iter().any(|command| first == command),
+            None => self.piped && self.program.is_some(),
+        }
+    }
+
+    pub fn program(&self) -> Result<&OsString> {
+        self.program
+            .as_ref()
+            .ok_or_else(|| Error::Config("Program (-p) must be specified!".into()))
     }
 }
 
+/// Stdin is a pipe, socket or file to stream assets from, and not a terminal
+/// or a device like the `/dev/null` cron and CI jobs run with.
+fn is_piped() -> bool {
+    let stdin = std::io::stdin();
+    !stdin.is_terminal()
+        && stdin
+            .as_fd()
+            .try_clone_to_owned()
+            .map(File::from)
+            .and_then(|f| f.metadata())
+            .is_ok_and(|meta| {
+                let ft = meta.file_type();
+                ft.is_file() || ft.is_fifo() || ft.is_socket()
+            })
+}
+
 pub fn get_config_file() -> Result<(PathBuf, File)> {
     let path = if let Ok(p) = current_exe() {
         if let Some(p) = p.parent() {
@@ -172,10 +432,10 @@ pub fn get_config_file() -> Result<(PathBuf, File)> {
 
     let file = OpenOptions::new()
         .read(true)
-        .write(true)
         .append(true)
         .create(true)
-        .open(&path)?;
+        .open(&path)
+        .with_path(&path)?;
 
     Ok((path, file))
 }
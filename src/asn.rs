@@ -0,0 +1,105 @@
+use cidr_utils::cidr::IpCidr;
+use std::{net::IpAddr, path::Path, str::FromStr};
+
+use crate::{ip::IpRange, utils::Input, Result};
+
+/// Datasets looked up in the hunt path:
+/// - `asn.tsv`: iptoasn.com format, `range_start range_end AS_number country_code AS_description`
+/// - `asn.csv`: MaxMind GeoLite2 ASN blocks, `network,autonomous_system_number,autonomous_system_organization`
+pub const FILES: [&str; 2] = ["asn.tsv", "asn.csv"];
+
+#[derive(Debug)]
+pub struct AsnDb {
+    /// Sorted, non-overlapping ranges
+    ranges: Vec<(IpRange, u32, String)>,
+}
+
+impl AsnDb {
+    /// Load the first dataset found in the hunt path, if any.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let Some(file) = FILES.iter().map(|f| path.join(f)).find(|f| f.is_file()) else {
+            return Ok(None);
+        };
+
+        let input = Input::open(&file)?;
+        let mut ranges = input
+            .split(|c| *c == b'\n')
+            .filter_map(|line| parse_line(&String::from_utf8_lossy(line)))
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|(range, _, _)| range.first);
+
+        Ok(Some(Self { ranges }))
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<(u32, &str)> {
+        let i = self
+            .ranges
+            .partition_point(|(range, _, _)| range.first <= ip);
+        let (range, asn, org) = self.ranges.get(i.checked_sub(1)?)?;
+        range
+            .contains(&IpRange {
+                first: ip,
+                last: ip,
+            })
+            .then_some((*asn, org.as_str()))
+    }
+}
+
+fn parse_line(line: &str) -> Option<(IpRange, u32, String)> {
+    let line = line.trim();
+    let (range, asn, org) = if line.contains('\t') {
+        let mut cols = line.split('\t');
+        let first = IpAddr::from_str(cols.next()?).ok()?;
+        let last = IpAddr::from_str(cols.next()?).ok()?;
+        let asn = cols.next()?.parse().ok()?;
+        let org = cols.nth(1).unwrap_or_default();
+        (IpRange { first, last }, asn, org)
+    } else {
+        let mut cols = line.splitn(3, ',');
+        let range = IpRange::from(&IpCidr::from_str(cols.next()?).ok()?);
+        let asn = cols.next()?.parse().ok()?;
+        let org = cols.next().unwrap_or_default().trim_matches('"');
+        (range, asn, org)
+    };
+
+    // iptoasn marks unrouted ranges with AS0
+    (asn != 0).then(|| (range, asn, org.to_string()))
+}
+
+/// Parse `AS13335` or `13335`.
+pub fn parse_asn(s: &str) -> std::result::Result<u32, String> {
+    s.trim_start_matches("AS")
+        .trim_start_matches("as")
+        .parse()
+        .map_err(|_| format!("{s:?} is not an ASN"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let db = AsnDb {
+            ranges: [
+                "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET",
+                "1.0.4.0/22,38803,\"Gtelecom, Pty Ltd\"",
+                "1.0.1.0\t1.0.3.255\t0\tNone\tNot routed",
+            ]
+            .iter()
+            .filter_map(|l| parse_line(l))
+            .collect(),
+        };
+
+        assert_eq!(
+            db.lookup("1.0.0.1".parse().unwrap()),
+            Some((13335, "CLOUDFLARENET"))
+        );
+        assert_eq!(
+            db.lookup("1.0.7.255".parse().unwrap()),
+            Some((38803, "Gtelecom, Pty Ltd"))
+        );
+        assert_eq!(db.lookup("1.0.2.1".parse().unwrap()), None);
+        assert_eq!(parse_asn("AS13335"), Ok(13335));
+    }
+}
@@ -0,0 +1,90 @@
+use cidr_utils::{cidr::IpCidr, utils::IpCidrCombiner};
+use std::{fs, net::IpAddr, path::Path};
+
+use crate::{error::WithPath, utils::Input, Result};
+
+/// Directory in the hunt path holding published ranges, one file per provider named
+/// after it, e.g. `aws.json` (ip-ranges.json), `gcp.json`, `azure.json`, `cloudflare.txt`.
+pub const DIR: &str = "providers";
+
+/// Providers treated as CDN edges by `--exclude-cdn`.
+const CDNS: [&str; 6] = [
+    "cloudflare",
+    "cloudfront",
+    "fastly",
+    "akamai",
+    "incapsula",
+    "sucuri",
+];
+
+pub fn is_cdn(provider: &str) -> bool {
+    let provider = provider.to_ascii_lowercase();
+    CDNS.iter().any(|cdn| provider.contains(cdn))
+}
+
+#[derive(Debug, Default)]
+pub struct Providers(Vec<(String, IpCidrCombiner)>);
+
+impl Providers {
+    pub fn load(path: &Path) -> Result<Self> {
+        let path = path.join(DIR);
+        let Ok(dir) = fs::read_dir(&path) else {
+            return Ok(Self::default());
+        };
+
+        let mut providers = Vec::new();
+        for e in dir {
+            let e = e.with_path(&path)?;
+            let file = e.path();
+            let Some(name) = file.file_stem().filter(|_| file.is_file()) else {
+                continue;
+            };
+
+            let mut ranges = IpCidrCombiner::new();
+            for cidr in cidrs(&Input::open(&file)?) {
+                ranges.push(cidr);
+            }
+            providers.push((name.to_string_lossy().to_lowercase(), ranges));
+        }
+        providers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self(providers))
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, ranges)| ranges.contains(ip))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// CIDRs anywhere in a JSON or text file, whatever the provider's schema is.
+fn cidrs(bytes: &[u8]) -> impl Iterator<Item = IpCidr> + '_ {
+    bytes
+        .split(|c| !(c.is_ascii_hexdigit() || matches!(c, b'.' | b':' | b'/')))
+        .filter(|token| token.contains(&b'/'))
+        .filter_map(|token| IpCidr::from_str(std::str::from_utf8(token).ok()?).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_from_json() {
+        let json = br#"{"prefixes": [{"ip_prefix": "3.5.140.0/22", "region": "ap-northeast-2"}],
+            "ipv6_prefixes": [{"ipv6_prefix": "2600:1f14::/35"}]}"#;
+        let mut ranges = IpCidrCombiner::new();
+        cidrs(json).for_each(|c| ranges.push(c));
+        let providers = Providers(vec![("aws".into(), ranges)]);
+
+        assert_eq!(providers.lookup("3.5.141.1".parse().unwrap()), Some("aws"));
+        assert_eq!(
+            providers.lookup("2600:1f14::1".parse().unwrap()),
+            Some("aws")
+        );
+        assert_eq!(providers.lookup("1.1.1.1".parse().unwrap()), None);
+        assert!(is_cdn("Cloudflare") && !is_cdn("aws"));
+    }
+}
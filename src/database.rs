@@ -1,25 +1,85 @@
 use cidr_utils::{cidr::IpCidr, utils::IpCidrCombiner};
 use fancy_regex::Regex;
+use itertools::Itertools;
 use std::{
-    collections::HashSet,
-    ffi::OsString,
+    collections::{BTreeSet, HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    fmt::Display,
     fs::{File, OpenOptions},
+    hash::Hash,
     os::unix::ffi::OsStrExt,
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
+    str::FromStr,
 };
 
 use crate::{
+    asn::AsnDb,
+    cloud::Providers,
+    error::WithPath,
+    psl::Psl,
     utils::{file_lines, force_write},
     Result,
 };
 
+#[derive(Debug, Default)]
+pub struct Count {
+    pub new: usize,
+    pub existing: usize,
+}
+
+/// What happened to the inserted (not loaded) assets.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub ip: Count,
+    pub domain: Count,
+    pub other: Count,
+    pub rejected: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (kind, count) in [
+            ("ip", &self.ip),
+            ("domain", &self.domain),
+            ("other", &self.other),
+        ] {
+            writeln!(f, "{kind}: {} new, {} existing", count.new, count.existing)?;
+        }
+        write!(f, "rejected: {}", self.rejected)
+    }
+}
+
 #[derive(Debug)]
 pub struct DataBase {
-    ip: (File, IpCidrCombiner),
+    /// Hunt path, where the datasets are
+    root: PathBuf,
+    path: PathBuf,
+    /// IPs and CIDRs as inserted
+    ip: (File, HashMap<IpCidr, Rc<OsString>>),
+    /// Merged view of `ip`
+    cidr: (File, IpCidrCombiner),
+    /// `AS<number> | <org>` of IPs, from the ASN dataset in the hunt path
+    asn: (File, HashMap<IpCidr, String>),
+    /// Cloud provider of IPs, from the published ranges in the hunt path
+    provider: (File, HashMap<IpCidr, String>),
+    /// Cloud provider of resolved hosts, stored with the IPs in `provider`
+    hosts: HashMap<String, String>,
+    /// Hosts resolved in structured input since the last write, with their IPs
+    resolved: HashMap<String, Vec<IpCidr>>,
+    /// ASN dataset and provider ranges, loaded on the first write that tags IPs
+    datasets: Option<(Option<AsnDb>, Providers)>,
     domain: (File, HashSet<Rc<OsString>>),
     other: (File, HashSet<Rc<OsString>>),
-    pub new: (File, HashSet<Rc<OsString>>),
+    /// Tools that found an asset, from structured input
+    source: (File, HashMap<String, BTreeSet<String>>),
+    psl: Psl,
+    /// Registrable domains of `domain`
+    apexes: HashSet<String>,
+    pub new: (File, Vec<Rc<OsString>>),
+    /// Apexes that had no domain before
+    pub new_apexes: Vec<String>,
+    pub stats: Stats,
 }
 
 impl DataBase {
@@ -27,20 +87,53 @@ impl DataBase {
         let new = OpenOptions::new()
             .append(true)
             .create(true)
-            .open(path.join("hust.log"))?;
+            .open(path.join("hust.log"))
+            .with_path(path.join("hust.log"))?;
+
+        let psl = Psl::load(path)?;
 
+        let root = path.to_path_buf();
         let path = path.join(program);
-        std::fs::create_dir_all(&path)?;
+        std::fs::create_dir_all(&path).with_path(&path)?;
 
         let (ip, ips) = file_lines(path.join("ip"))?;
+        let (cidr, _) = file_lines(path.join("cidr"))?;
+        let (asn, asns) = file_lines(path.join("asn"))?;
+        let (provider, tagged) = file_lines(path.join("provider"))?;
         let (domain, domains) = file_lines(path.join("domain"))?;
         let (other, others) = file_lines(path.join("other"))?;
+        let (source, sources) = file_lines(path.join("source"))?;
 
         let db = Self {
-            ip: (ip, IpCidrCombiner::new()),
+            root,
+            path,
+            ip: (ip, HashMap::new()),
+            cidr: (cidr, IpCidrCombiner::new()),
+            asn: (asn, tags(asns.iter().map(|l| l.as_bytes()))),
+            provider: (provider, tags(tagged.iter().map(|l| l.as_bytes()))),
+            hosts: tags(tagged.iter().map(|l| l.as_bytes())),
+            resolved: HashMap::new(),
+            datasets: None,
             domain: (domain, HashSet::new()),
             other: (other, HashSet::new()),
-            new: (new, HashSet::new()),
+            source: (
+                source,
+                sources
+                    .iter()
+                    .filter_map(|line| {
+                        let (asset, sources) = line.to_str()?.split_once(" | ")?;
+                        Some((
+                            asset.to_string(),
+                            sources.split(',').map(String::from).collect(),
+                        ))
+                    })
+                    .collect(),
+            ),
+            psl,
+            apexes: HashSet::new(),
+            new: (new, Vec::new()),
+            new_apexes: Vec::new(),
+            stats: Stats::default(),
         };
 
         Ok(db
@@ -54,50 +147,309 @@ impl DataBase {
 
         for arg in args {
             let arg = Rc::new(arg);
-            if let Ok(ip) =
+            let (count, is_new) = if let Ok(ip) =
                 IpCidr::try_from(unsafe { std::str::from_utf8_unchecked(arg.as_bytes()) })
             {
-                if !self.ip.1.contains(ip.first_as_ip_addr())
-                    && !self.ip.1.contains(ip.last_as_ip_addr())
-                {
-                    self.ip.1.push(ip);
-                    if new {
-                        self.new.1.insert(arg);
+                let is_new = !self.ip.1.contains_key(&ip);
+                if is_new {
+                    self.ip.1.insert(ip, Rc::clone(&arg));
+                    self.cidr.1.push(ip);
+                }
+                (&mut self.stats.ip, is_new)
+            } else {
+                match r.is_match(&arg.to_string_lossy()) {
+                    Ok(true) => {
+                        let is_new = self.domain.1.insert(Rc::clone(&arg));
+                        if is_new {
+                            if let Some(apex) = self.psl.apex(&arg.to_string_lossy()) {
+                                if !self.apexes.contains(&apex) {
+                                    self.apexes.insert(apex.clone());
+                                    if new {
+                                        self.new_apexes.push(apex);
+                                    }
+                                }
+                            }
+                        }
+                        (&mut self.stats.domain, is_new)
+                    }
+                    Ok(false) => (&mut self.stats.other, self.other.1.insert(Rc::clone(&arg))),
+                    // Regex gave up (backtrack limit)
+                    Err(_) => {
+                        if new {
+                            self.stats.rejected += 1;
+                        }
+                        continue;
                     }
                 }
-            } else if r.is_match(&arg.to_string_lossy()).unwrap() {
-                if self.domain.1.insert(Rc::clone(&arg)) && new {
-                    self.new.1.insert(arg);
+            };
+
+            if new {
+                if is_new {
+                    count.new += 1;
+                    self.new.1.push(arg);
+                } else {
+                    count.existing += 1;
                 }
-            } else if self.other.1.insert(Rc::clone(&arg)) && new {
-                self.new.1.insert(arg);
             }
         }
 
         self
     }
 
+    /// Record which tools found `asset`.
+    pub fn tag_sources(&mut self, asset: &str, sources: &[String]) {
+        if !sources.is_empty() {
+            self.source
+                .1
+                .entry(asset.to_string())
+                .or_default()
+                .extend(sources.iter().map(|s| s.replace([',', '\n'], " ")));
+        }
+    }
+
+    /// Record the IPs `host` resolved to, it's tagged with their provider on write.
+    pub fn tag_resolved(&mut self, host: &str, ips: &[String]) {
+        let ips = ips.iter().filter_map(|ip| IpCidr::from_str(ip).ok());
+        self.resolved
+            .entry(host.to_string())
+            .or_default()
+            .extend(ips);
+    }
+
+    /// Store of a stored asset: `ip`, `domain` or `other`.
+    pub fn kind(&self, asset: &OsString) -> &'static str {
+        if self.domain.1.contains(asset) {
+            "domain"
+        } else if self.other.1.contains(asset) {
+            "other"
+        } else {
+            "ip"
+        }
+    }
+
+    /// Remove `assets` from every store, return how many were there.
+    pub fn remove(&mut self, assets: &HashSet<OsString>) -> usize {
+        let before = self.ip.1.len() + self.domain.1.len() + self.other.1.len();
+
+        self.ip
+            .1
+            .retain(|_, asset| !assets.contains(asset.as_ref()));
+        self.asn.1.retain(|ip, _| self.ip.1.contains_key(ip));
+        self.provider.1.retain(|ip, _| self.ip.1.contains_key(ip));
+        self.cidr.1 = IpCidrCombiner::new();
+        for ip in self.ip.1.keys() {
+            self.cidr.1.push(*ip);
+        }
+        self.domain
+            .1
+            .retain(|asset| !assets.contains(asset.as_ref()));
+        self.other
+            .1
+            .retain(|asset| !assets.contains(asset.as_ref()));
+        self.source
+            .1
+            .retain(|asset, _| !assets.contains(OsStr::new(asset)));
+
+        before - (self.ip.1.len() + self.domain.1.len() + self.other.1.len())
+    }
+
+    /// Store files of the program that aren't empty.
+    pub fn files(&self) -> Vec<PathBuf> {
+        ["ip", "cidr", "asn", "provider", "domain", "other", "source"]
+            .map(|kind| self.path.join(kind))
+            .into_iter()
+            .filter(|file| file.metadata().is_ok_and(|m| m.len() > 0))
+            .collect()
+    }
+
+    /// Tag the untagged IPs, stored ones included, with their ASN and provider,
+    /// and resolved hosts with the provider of their IPs.
+    fn tag(&mut self) -> Result<()> {
+        let (asn_db, providers) = match self.datasets.take() {
+            Some(datasets) => datasets,
+            None => (AsnDb::load(&self.root)?, Providers::load(&self.root)?),
+        };
+
+        for ip in self.ip.1.keys() {
+            if let Some((asn, org)) = asn_db
+                .as_ref()
+                .filter(|_| !self.asn.1.contains_key(ip))
+                .and_then(|asn_db| asn_db.lookup(ip.first_as_ip_addr()))
+            {
+                self.asn.1.insert(*ip, format!("AS{asn} | {org}"));
+            }
+            if let Some(provider) = providers
+                .lookup(ip.first_as_ip_addr())
+                .filter(|_| !self.provider.1.contains_key(ip))
+            {
+                self.provider.1.insert(*ip, provider.to_string());
+            }
+        }
+        for (host, ips) in self.resolved.drain() {
+            if let Some(provider) = ips
+                .iter()
+                .find_map(|ip| providers.lookup(ip.first_as_ip_addr()))
+            {
+                self.hosts.insert(host, provider.to_string());
+            }
+        }
+
+        self.datasets = Some((asn_db, providers));
+        Ok(())
+    }
+
     pub fn write(&mut self) -> Result<()> {
+        // The datasets are big, only loaded when there are IPs or resolved hosts
+        if self.stats.ip.new + self.stats.ip.existing > 0 || !self.resolved.is_empty() {
+            self.tag()?;
+        }
+
         force_write(
             &mut self.ip.0,
-            self.ip
+            self.ip.1.values().map(|s| s.to_string_lossy()),
+        )
+        .with_path(self.path.join("ip"))?;
+
+        force_write(
+            &mut self.cidr.0,
+            self.cidr
                 .1
                 .get_ipv4_cidrs()
                 .iter()
                 .map(|c| c.to_string())
-                .chain(self.ip.1.get_ipv6_cidrs().iter().map(|c| c.to_string())),
-        )?;
+                .chain(self.cidr.1.get_ipv6_cidrs().iter().map(|c| c.to_string())),
+        )
+        .with_path(self.path.join("cidr"))?;
+
+        let of_ips = |tags: &HashMap<IpCidr, String>| {
+            tags.iter()
+                .filter_map(|(ip, tag)| {
+                    let ip = self.ip.1.get(ip)?;
+                    Some(format!("{} | {tag}", ip.to_string_lossy()))
+                })
+                .collect::<Vec<_>>()
+        };
+        force_write(&mut self.asn.0, of_ips(&self.asn.1)).with_path(self.path.join("asn"))?;
+        let hosts = self
+            .hosts
+            .iter()
+            .filter(|(host, _)| self.domain.1.contains(&OsString::from(host)))
+            .map(|(host, tag)| format!("{host} | {tag}"));
+        let providers = of_ips(&self.provider.1)
+            .into_iter()
+            .chain(hosts)
+            .collect_vec();
+        force_write(&mut self.provider.0, providers).with_path(self.path.join("provider"))?;
 
         force_write(
             &mut self.domain.0,
             self.domain.1.iter().map(|s| s.to_string_lossy()),
-        )?;
+        )
+        .with_path(self.path.join("domain"))?;
 
         force_write(
             &mut self.other.0,
             self.other.1.iter().map(|s| s.to_string_lossy()),
-        )?;
+        )
+        .with_path(self.path.join("other"))?;
+
+        force_write(
+            &mut self.source.0,
+            self.source
+                .1
+                .iter()
+                .map(|(asset, sources)| format!("{asset} | {}", sources.iter().join(","))),
+        )
+        .with_path(self.path.join("source"))?;
 
         Ok(())
     }
 }
+
+/// Parse `asset | tag` lines of a tag store, keyed by IP or by host.
+pub fn tags<'a, K: FromStr + Hash + Eq>(
+    lines: impl IntoIterator<Item = &'a [u8]>,
+) -> HashMap<K, String> {
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let (asset, tag) = std::str::from_utf8(line).ok()?.split_once(" | ")?;
+            Some((K::from_str(asset).ok()?, tag.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_dir;
+
+    #[test]
+    fn import_stats() {
+        let path = test_dir("db");
+
+        let args = ["a.example.com", "a.example.com", "1.2.3.4", "foo"].map(OsString::from);
+        let db = DataBase::init(&path, &"x".into())
+            .unwrap()
+            .import(args.to_vec(), true);
+
+        assert_eq!((db.stats.domain.new, db.stats.domain.existing), (1, 1));
+        assert_eq!((db.stats.ip.new, db.stats.other.new), (1, 1));
+        assert_eq!(db.new.1.len(), 3);
+    }
+
+    #[test]
+    fn keep_raw_ips() {
+        let path = test_dir("raw");
+
+        let args = ["1.2.3.0/24", "1.2.3.4", "1.2.3.4/32", "1.2.3.5"].map(OsString::from);
+        let mut db = DataBase::init(&path, &"x".into())
+            .unwrap()
+            .import(args.to_vec(), true);
+        db.write().unwrap();
+
+        assert_eq!(db.stats.ip.new, 3);
+        assert_eq!(db.stats.ip.existing, 1);
+        assert_eq!(db.ip.1.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(path.join("x/cidr")).unwrap(),
+            "1.2.3.0/24"
+        );
+    }
+
+    #[test]
+    fn tags_of_stored_ips() {
+        let path = test_dir("asn");
+        let insert = |ip: &str| {
+            let mut db = DataBase::init(&path, &"x".into())
+                .unwrap()
+                .import(vec![ip.into()], true);
+            db.write().unwrap();
+        };
+
+        insert("1.0.0.1");
+        assert_eq!(std::fs::read_to_string(path.join("x/asn")).unwrap(), "");
+        std::fs::create_dir(path.join("providers")).unwrap();
+        std::fs::write(path.join("providers/cloudflare.txt"), "1.0.0.0/24\n").unwrap();
+
+        std::fs::write(
+            path.join("asn.tsv"),
+            "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n",
+        )
+        .unwrap();
+        insert("1.0.0.2");
+        let asn = std::fs::read_to_string(path.join("x/asn")).unwrap();
+        assert!(asn.contains("1.0.0.1 | AS13335 | CLOUDFLARENET"));
+        assert!(asn.contains("1.0.0.2 | AS13335 | CLOUDFLARENET"));
+        let provider = std::fs::read_to_string(path.join("x/provider")).unwrap();
+        assert!(provider.contains("1.0.0.1 | cloudflare"));
+
+        let mut db = DataBase::init(&path, &"x".into())
+            .unwrap()
+            .import(vec!["a.example.com".into()], true);
+        db.tag_resolved("a.example.com", &["1.0.0.3".into()]);
+        db.write().unwrap();
+        let provider = std::fs::read_to_string(path.join("x/provider")).unwrap();
+        assert!(provider.contains("a.example.com | cloudflare"));
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, FixedOffset};
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    args::Args,
+    error::Error,
+    log, programs, snapshot,
+    utils::{Input, Memfind},
+    Result,
+};
+
+/// Stores compared by `diff`, the others are derived from them.
+pub const STORES: [&str; 3] = ["ip", "domain", "other"];
+
+/// `hust diff`: assets added since a date, or changes between snapshots.
+pub fn run(cfg: &Args) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+
+    if !cfg.diff.snapshots.is_empty() {
+        let tmp = TempDir(std::env::temp_dir().join(format!("hust.diff.{}", std::process::id())));
+        let mut dirs = Vec::new();
+        for (i, snapshot) in cfg.diff.snapshots.iter().enumerate() {
+            dirs.push(snapshot_dir(cfg, snapshot, &tmp.0.join(i.to_string()))?);
+        }
+        let (a, b) = match dirs.as_slice() {
+            [a, b] => (a.clone(), b.clone()),
+            [a] if cfg.program.is_some() => (a.clone(), cfg.path.join(cfg.program()?)),
+            _ => {
+                return Err(Error::Config(
+                    "Two snapshots, or one and -p, are needed: --snapshot a --snapshot b".into(),
+                ))
+            }
+        };
+        for (store, added, line) in stores(&a, &b)? {
+            let sign = if added { '+' } else { '-' };
+            if cfg.verbosity {
+                writeln!(stdout, "{sign} {store} | {line}")?;
+            } else {
+                writeln!(stdout, "{sign} {line}")?;
+            }
+        }
+        return Ok(());
+    }
+
+    let program = cfg.program()?;
+    programs::check_exists(&cfg.path, program)?;
+    let from = cfg
+        .since
+        .ok_or_else(|| Error::Config("--since must be specified!".into()))?;
+
+    let logs = cfg.log.read(&cfg.path, Some(program))?;
+    for (asset, date) in since(&cfg.path, &logs, program, from)? {
+        if cfg.verbosity {
+            writeln!(stdout, "{asset} | {}", date.to_rfc3339())?;
+        } else {
+            writeln!(stdout, "{asset}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removed with its content when dropped, whatever returns early.
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A program directory, or a snapshot id extracted to `tmp`.
+fn snapshot_dir(cfg: &Args, snapshot: &OsString, tmp: &Path) -> Result<PathBuf> {
+    if Path::new(snapshot).is_dir() {
+        return Ok(PathBuf::from(snapshot));
+    }
+    let id = snapshot.to_string_lossy();
+    snapshot::unpack(&cfg.path, &id, tmp)?;
+    match (snapshot::program(&id), &cfg.program) {
+        (Some(_), _) => Ok(tmp.to_path_buf()),
+        (None, Some(program)) => Ok(tmp.join(program)),
+        (None, None) => Err(Error::Config(format!(
+            "Snapshot {id:?} is of the whole hunt path, select a program with -p"
+        ))),
+    }
+}
+
+/// Lines of a store, empty if it doesn't exist.
+fn lines(path: &Path) -> Result<HashSet<String>> {
+    if !path.is_file() {
+        return Ok(HashSet::new());
+    }
+    Ok(Input::open(path)?
+        .find(&[])
+        .into_iter()
+        .map(|line| String::from_utf8_lossy(line).to_string())
+        .collect())
+}
+
+/// Assets of `program` logged in `logs` since `since` that are still in its stores, oldest first.
+pub fn since(
+    path: &Path,
+    logs: &[PathBuf],
+    program: &OsStr,
+    since: DateTime<FixedOffset>,
+) -> Result<Vec<(String, DateTime<FixedOffset>)>> {
+    let dir = path.join(program);
+    let mut stored = HashSet::new();
+    for store in STORES {
+        stored.extend(lines(&dir.join(store))?);
+    }
+
+    let program = program.to_string_lossy();
+    let mut seen = HashSet::new();
+    let mut added = Vec::new();
+    log::each(logs, |entry| {
+        if entry.program == program
+            && entry.date >= since
+            && stored.contains(entry.asset.as_ref())
+            && seen.insert(entry.asset.to_string())
+        {
+            added.push((entry.asset.to_string(), entry.date));
+        }
+    })?;
+    added.sort_by_key(|(_, date)| *date);
+
+    Ok(added)
+}
+
+/// Added (`true`) and removed (`false`) lines per store from program directory `a` to `b`.
+pub fn stores(a: &Path, b: &Path) -> Result<Vec<(&'static str, bool, String)>> {
+    let mut changes = Vec::new();
+    for store in STORES {
+        let (a, b) = (lines(&a.join(store))?, lines(&b.join(store))?);
+        let mut added = b.difference(&a).cloned().collect::<Vec<_>>();
+        let mut removed = a.difference(&b).cloned().collect::<Vec<_>>();
+        added.sort();
+        removed.sort();
+        changes.extend(removed.into_iter().map(|line| (store, false, line)));
+        changes.extend(added.into_iter().map(|line| (store, true, line)));
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn since_and_stores() {
+        let path = crate::utils::tests::test_dir("diff");
+        fs::create_dir_all(path.join("x")).unwrap();
+        fs::create_dir_all(path.join("y")).unwrap();
+        fs::write(
+            path.join("hust.log"),
+            "x | old.com | Mon, 05 Oct 2026 07:00:00 +0000\n\
+             x | b.com | Sat, 17 Oct 2026 07:00:00 +0000\n\
+             x | a.com | Fri, 16 Oct 2026 07:00:00 +0000\n\
+             x | gone.com | Fri, 16 Oct 2026 07:00:00 +0000\n\
+             y | c.com | Fri, 16 Oct 2026 07:00:00 +0000\n",
+        )
+        .unwrap();
+        fs::write(path.join("x/domain"), "old.com\na.com\nb.com").unwrap();
+        fs::write(path.join("y/domain"), "old.com\nc.com").unwrap();
+
+        let date = DateTime::parse_from_rfc3339("2026-10-12T00:00:00Z").unwrap();
+        let added = since(&path, &[path.join("hust.log")], "x".as_ref(), date).unwrap();
+        assert_eq!(
+            added
+                .iter()
+                .map(|(asset, _)| asset.as_str())
+                .collect::<Vec<_>>(),
+            ["a.com", "b.com"]
+        );
+
+        assert_eq!(
+            stores(&path.join("x"), &path.join("y")).unwrap(),
+            [
+                ("domain", false, "a.com".to_string()),
+                ("domain", false, "b.com".to_string()),
+                ("domain", true, "c.com".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Bad command line or config file.
+    Config(String),
+    /// Reading or writing a file failed, `path` is the offending file if known.
+    Io {
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    /// An input could not be understood.
+    Parse(String),
+    /// Sending a notification failed.
+    Network(reqwest::Error),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => 2,
+            Error::Io { .. } => 3,
+            Error::Parse(_) => 4,
+            Error::Network(_) => 5,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Config(msg) => write!(f, "Config error: {msg}"),
+            Error::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "IO error: {}: {source}", path.to_string_lossy()),
+            Error::Io { path: None, source } => write!(f, "IO error: {source}"),
+            Error::Parse(msg) => write!(f, "Parse error: {msg}"),
+            Error::Network(err) => write!(f, "Network error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Network(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Self {
+        Error::Io { path: None, source }
+    }
+}
+
+impl From<lexopt::Error> for Error {
+    fn from(err: lexopt::Error) -> Self {
+        Error::Config(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Network(err)
+    }
+}
+
+/// Attach the offending file to an IO error.
+pub trait WithPath<T> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T>;
+}
+
+impl<T> WithPath<T> for std::io::Result<T> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T> {
+        self.map_err(|source| Error::Io {
+            path: Some(path.as_ref().to_path_buf()),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_error_names_file() {
+        let err = std::fs::File::open("/nonexistent/hust")
+            .with_path("/nonexistent/hust")
+            .unwrap_err();
+
+        assert_eq!(err.exit_code(), 3);
+        assert!(err.to_string().contains("/nonexistent/hust"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::{io::Write, str::FromStr};
+
+use crate::{
+    args::{Args, Filter},
+    error::Error,
+    ip::IpRange,
+    log, programs, search,
+    utils::{Input, Memfind},
+};
+
+/// `hust export -p program`: every asset of a program with its kind and first seen time.
+pub fn run(cfg: &Args) -> crate::Result<()> {
+    let program = cfg.program()?;
+    let format = cfg
+        .format
+        .as_deref()
+        .unwrap_or("json")
+        .parse::<Format>()
+        .map_err(Error::Config)?;
+    let dir = programs::selected(cfg)?.remove(0).path();
+
+    let first_seen = log::first_seen(&cfg.log.read(&cfg.path, Some(program))?, program)?;
+    let filter = Filter {
+        merged: cfg.filter.merged,
+        exclude_cdn: cfg.filter.exclude_cdn,
+        ..Default::default()
+    };
+    let tagged = search::Tagged::load(&dir, &filter, None)?;
+
+    let ip = if cfg.filter.merged { "cidr" } else { "ip" };
+    let mut writer = Writer::new(format, std::io::stdout().lock())?;
+    for (kind, store) in [("ip", ip), ("domain", "domain"), ("other", "other")] {
+        let Ok(input) = Input::open(dir.join(store)) else {
+            continue;
+        };
+        for asset in input.find(&[]) {
+            let asset = String::from_utf8_lossy(asset);
+            let excluded = match kind {
+                "ip" => {
+                    IpRange::from_str(&asset).is_ok_and(|range| !tagged.matches(&asset, &range))
+                }
+                "domain" => !tagged.matches_host(&asset),
+                _ => false,
+            };
+            if excluded {
+                continue;
+            }
+
+            writer.write(&Record {
+                program: &program.to_string_lossy(),
+                kind,
+                asset: &asset,
+                first_seen: first_seen.get(asset.as_ref()).map(|d| d.to_rfc3339()),
+            })?;
+        }
+    }
+
+    Ok(writer.finish()?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    Ndjson,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            _ => Err(format!("{s:?} is not one of json, csv, ndjson")),
+        }
+    }
+}
+
+pub struct Record<'a> {
+    pub program: &'a str,
+    pub kind: &'a str,
+    pub asset: &'a str,
+    /// RFC 3339
+    pub first_seen: Option<String>,
+}
+
+/// Writes records one by one, so huge programs aren't held in memory.
+pub struct Writer<W: Write> {
+    format: Format,
+    out: W,
+    count: usize,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(format: Format, mut out: W) -> std::io::Result<Self> {
+        match format {
+            Format::Json => out.write_all(b"[")?,
+            Format::Csv => out.write_all(b"program,kind,asset,first_seen\n")?,
+            Format::Ndjson => (),
+        }
+        Ok(Self {
+            format,
+            out,
+            count: 0,
+        })
+    }
+
+    pub fn write(&mut self, record: &Record) -> std::io::Result<()> {
+        match self.format {
+            Format::Json | Format::Ndjson => {
+                let json = serde_json::json!({
+                    "program": record.program,
+                    "kind": record.kind,
+                    "asset": record.asset,
+                    "first_seen": record.first_seen,
+                });
+                if self.format == Format::Json {
+                    if self.count > 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    write!(self.out, "\n  {json}")?;
+                } else {
+                    writeln!(self.out, "{json}")?;
+                }
+            }
+            Format::Csv => writeln!(
+                self.out,
+                "{},{},{},{}",
+                csv(record.program),
+                record.kind,
+                csv(record.asset),
+                record.first_seen.as_deref().unwrap_or_default()
+            )?,
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        if self.format == Format::Json {
+            self.out.write_all(b"\n]\n")?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Quote a CSV field if needed.
+fn csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format: Format) -> String {
+        let mut out = Vec::new();
+        let mut writer = Writer::new(format, &mut out).unwrap();
+        for (kind, asset) in [("domain", "a.example.com"), ("other", "a,\"b\"")] {
+            writer
+                .write(&Record {
+                    program: "x",
+                    kind,
+                    asset,
+                    first_seen: None,
+                })
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let json: serde_json::Value = serde_json::from_str(&export(Format::Json)).unwrap();
+        assert_eq!(json[1]["asset"], "a,\"b\"");
+
+        assert_eq!(export(Format::Ndjson).lines().count(), 2);
+        assert_eq!(
+            export(Format::Csv),
+            "program,kind,asset,first_seen\nx,domain,a.example.com,\nx,other,\"a,\"\"b\"\"\",\n"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::Utc;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::{self, Write},
+    os::unix::process::CommandExt,
+    path::Path,
+    process::{Command, Stdio},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{utils, Error};
+
+/// Commands are killed after it, unless `hook_timeout` is set.
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// New assets a hook runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Event {
+    /// Any kind
+    New,
+    Ip,
+    Domain,
+    Other,
+    /// Registrable domains that had no domain before
+    Apex,
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on_new" => Ok(Self::New),
+            "on_new_ip" => Ok(Self::Ip),
+            "on_new_domain" => Ok(Self::Domain),
+            "on_new_other" => Ok(Self::Other),
+            "on_new_apex" => Ok(Self::Apex),
+            _ => Err(format!(
+                "{s:?} is not one of on_new, on_new_ip, on_new_domain, on_new_other, on_new_apex"
+            )),
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::New => "on_new",
+            Self::Ip => "on_new_ip",
+            Self::Domain => "on_new_domain",
+            Self::Other => "on_new_other",
+            Self::Apex => "on_new_apex",
+        })
+    }
+}
+
+/// A `[program.]on_new_<kind> = command` line of the config.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hook {
+    /// All programs if `None`
+    pub program: Option<String>,
+    pub event: Event,
+    pub command: String,
+}
+
+impl Hook {
+    /// `None` if `key` isn't a hook, an error if it looks like a mistyped one.
+    pub fn parse(key: &str, command: &str) -> Result<Option<Self>, String> {
+        let (program, event) = match key.rsplit_once('.') {
+            Some((program, event)) => (Some(program.to_string()), event),
+            None => (None, key),
+        };
+        match event.parse() {
+            Ok(event) => Ok(Some(Self {
+                program,
+                event,
+                command: command.trim().to_string(),
+            })),
+            Err(err) if key.starts_with("on_new") || key.contains(".on_new") => Err(err),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Commands for `event` in `program`, its own hooks replace the ones of all programs.
+pub fn commands<'a>(hooks: &'a [Hook], program: &str, event: Event) -> Vec<&'a str> {
+    let of = |program: Option<&str>| {
+        hooks
+            .iter()
+            .filter(|hook| hook.event == event && hook.program.as_deref() == program)
+            .map(|hook| hook.command.as_str())
+            .collect::<Vec<_>>()
+    };
+    match of(Some(program)) {
+        commands if commands.is_empty() => of(None),
+        commands => commands,
+    }
+}
+
+/// Run the hooks of `program` on its new assets, one run per command with all of its assets.
+///
+/// The assets are already stored, so failures are only reported and appended to `hooks.log`.
+pub fn fire(
+    path: &Path,
+    hooks: &[Hook],
+    program: &str,
+    new: &BTreeMap<Event, Vec<String>>,
+    timeout: Duration,
+) {
+    for (event, assets) in new.iter().filter(|(_, assets)| !assets.is_empty()) {
+        for command in commands(hooks, program, *event) {
+            let input = format!("{}\n", assets.join("\n"));
+            if let Err(err) = run(command, &input, timeout) {
+                report(path, program, event, command, err);
+            }
+        }
+    }
+}
+
+/// Report a failed command of `program` run on `what` on stderr and in `hooks.log`.
+pub fn report(path: &Path, program: &str, what: impl Display, command: &str, err: io::Error) {
+    let err = Error::from(err);
+    eprintln!("{program} {what}: {err}");
+    let line = format!(
+        "{} | {program} | {what} | {command} | {err}",
+        Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    );
+    if let Err(err) = utils::append(path.join("hooks.log"), &line) {
+        eprintln!("{err}");
+    }
+}
+
+/// Run `command` with `sh -c`, feeding `input` on its stdin, killed with
+/// what it started after `timeout`.
+///
+/// Its output goes to our stderr, stdout is kept for the assets. A failure or timeout is an error.
+pub fn run(command: &str, input: &str, timeout: Duration) -> io::Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(io::stderr()))
+        .process_group(0)
+        .spawn()?;
+
+    // Written aside, so a command that doesn't read stdin can't block us
+    let mut stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            // A command may exit without reading all of it
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            // Its own group, so the commands of a pipeline are killed too
+            unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{command:?} timed out after {timeout:?}"),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let _ = writer.join();
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{command:?} failed: {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_program() {
+        let hooks = [
+            Hook::parse("on_new_domain", " httpx -silent").unwrap(),
+            Hook::parse("x.y.on_new_domain", "dnsx").unwrap(),
+            Hook::parse("x.y.on_new_ip", "naabu").unwrap(),
+        ]
+        .map(Option::unwrap);
+        assert_eq!(Hook::parse("delimiter", ","), Ok(None));
+        assert!(Hook::parse("on_new_domian", "httpx").is_err());
+        assert!(Hook::parse("x.on_new_ips", "naabu").is_err());
+
+        assert_eq!(commands(&hooks, "z", Event::Domain), ["httpx -silent"]);
+        assert_eq!(commands(&hooks, "x.y", Event::Domain), ["dnsx"]);
+        assert_eq!(commands(&hooks, "x.y", Event::Ip), ["naabu"]);
+        assert!(commands(&hooks, "z", Event::Other).is_empty());
+    }
+
+    #[test]
+    fn status_and_timeout() {
+        let second = Duration::from_secs(1);
+        assert!(run("grep -q a.com", "a.com\nb.com\n", second).is_ok());
+        assert!(run("grep -q c.com", "a.com\n", second).is_err());
+        assert_eq!(
+            run("sleep 5", "", Duration::from_millis(100))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::TimedOut
+        );
+
+        // What the command started is killed too
+        let pid = crate::utils::tests::test_dir("hook").join("pid");
+        let command = format!("sleep 5 & echo $! > {}; wait", pid.to_string_lossy());
+        assert!(run(&command, "", Duration::from_millis(200)).is_err());
+        let stat = format!(
+            "/proc/{}/stat",
+            std::fs::read_to_string(&pid).unwrap().trim()
+        );
+        let alive = || std::fs::read_to_string(&stat).is_ok_and(|stat| !stat.contains(") Z "));
+        let start = Instant::now();
+        while alive() && start.elapsed() < second {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!alive());
+    }
+}
@@ -0,0 +1,214 @@
+use itertools::Itertools;
+use std::{collections::BTreeMap, ffi::OsString, io::Write, os::unix::ffi::OsStrExt};
+
+use crate::{
+    args::Args, database::DataBase as db, error::WithPath, hook, log,
+    notification::send_notification, snapshot, tools, utils, Result,
+};
+
+/// Number of piped lines imported at once, so huge inputs stream in bounded memory.
+const BATCH: usize = 100_000;
+
+pub fn run(mut args: Args, program: OsString) -> Result<()> {
+    let start = std::time::Instant::now();
+    let batch = log::batch_id();
+    for log in args.log.write(&args.path, &program) {
+        if let Some(segment) = log::rotate(&log, args.insert.rotation, chrono::Utc::now())? {
+            if args.verbosity {
+                eprintln!("Log rotated to {}", segment.to_string_lossy());
+            }
+        }
+    }
+    let mut new = BTreeMap::new();
+    let mut snapshot_above = args.insert.snapshot_above;
+    let mut db = db::init(&args.path, &program)?.import(std::mem::take(&mut args.args), true);
+    flush(
+        &args,
+        &program,
+        &batch,
+        &mut db,
+        &mut new,
+        &mut snapshot_above,
+    )?;
+
+    if args.piped {
+        let lines = utils::lines(std::io::stdin().lock());
+        for chunk in &lines.chunks(BATCH) {
+            let mut assets = Vec::new();
+            for line in chunk {
+                let line = line.with_path("stdin")?;
+                match tools::Record::parse(&line) {
+                    Some(record) => {
+                        if let Some(host) = &record.host {
+                            db.tag_sources(host, &record.sources);
+                        }
+                        if let Some((host, ips)) = &record.resolved {
+                            db.tag_resolved(host, ips);
+                        }
+                        assets.extend(record.assets.into_iter().map(OsString::from));
+                    }
+                    // Broken JSON isn't split into tokens either
+                    None if args.insert.format == tools::Format::Jsonl
+                        || line.trim_ascii().starts_with(b"{") =>
+                    {
+                        if !line.trim_ascii().is_empty() {
+                            db.stats.rejected += 1;
+                        }
+                    }
+                    None => assets.extend(args.insert.split.split(&line)),
+                }
+            }
+            db = db.import(assets, true);
+            flush(
+                &args,
+                &program,
+                &batch,
+                &mut db,
+                &mut new,
+                &mut snapshot_above,
+            )?;
+        }
+    }
+
+    hook::fire(
+        &args.path,
+        &args.hooks,
+        &program.to_string_lossy(),
+        &new,
+        args.hook_timeout,
+    );
+
+    if args.verbosity {
+        eprintln!(
+            "{}\nBatch: {batch}\nFiles: {}\nTime: {:?}",
+            db.stats,
+            db.files()
+                .iter()
+                .chain(Some(&args.path.join("hust.log")))
+                .map(|p| p.to_string_lossy())
+                .join(", "),
+            start.elapsed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Write the stores, then emit what is new since the last call, so a failure
+/// or a killed stream doesn't announce assets that aren't stored.
+///
+/// The program is snapshotted once, before the write that brings the insert
+/// to `snapshot_above` new assets.
+fn flush(
+    cfg: &Args,
+    program: &OsString,
+    batch: &str,
+    db: &mut db,
+    hooks: &mut BTreeMap<hook::Event, Vec<String>>,
+    snapshot_above: &mut Option<usize>,
+) -> Result<()> {
+    let count = db.stats.ip.new + db.stats.domain.new + db.stats.other.new;
+    if snapshot_above.is_some_and(|above| count >= above) {
+        *snapshot_above = None;
+        let id = snapshot::create(&cfg.path, Some(program))?;
+        if !cfg.quiet {
+            eprintln!("Snapshot {id} taken before writing {count} new assets");
+        }
+    }
+    db.write()?;
+    emit(cfg, program, batch, db, hooks)
+}
+
+/// Print, log and notify the assets that are new since the last call,
+/// and keep them in `hooks` per event if there are hooks.
+fn emit(
+    cfg: &Args,
+    program: &OsString,
+    batch: &str,
+    db: &mut db,
+    hooks: &mut BTreeMap<hook::Event, Vec<String>>,
+) -> Result<()> {
+    let args = std::mem::take(&mut db.new.1);
+    let apexes = std::mem::take(&mut db.new_apexes);
+    if !args.is_empty() {
+        if !cfg.hooks.is_empty() {
+            for arg in args.iter() {
+                let event = match db.kind(arg) {
+                    "domain" => hook::Event::Domain,
+                    "other" => hook::Event::Other,
+                    _ => hook::Event::Ip,
+                };
+                for event in [hook::Event::New, event] {
+                    hooks
+                        .entry(event)
+                        .or_default()
+                        .push(arg.to_string_lossy().to_string());
+                }
+            }
+            hooks
+                .entry(hook::Event::Apex)
+                .or_default()
+                .extend(apexes.iter().cloned());
+        }
+
+        if cfg.verbosity && !apexes.is_empty() {
+            eprintln!("New apex: {}", apexes.join(", "));
+        }
+
+        if !cfg.quiet {
+            let mut stdout = std::io::stdout().lock();
+            for arg in args.iter() {
+                stdout.write_all(arg.as_bytes())?;
+                stdout.write_all(b"\n")?;
+            }
+        }
+
+        let lines = args
+            .iter()
+            .map(|str| {
+                log::Entry {
+                    program: program.to_string_lossy(),
+                    asset: str.to_string_lossy(),
+                    date: chrono::Utc::now().fixed_offset(),
+                    batch: Some(batch.into()),
+                    reverted: false,
+                }
+                .to_string()
+            })
+            .join("\n");
+        let append_res = cfg
+            .log
+            .write(&cfg.path, program)
+            .into_iter()
+            .try_for_each(|log| utils::append(log, &lines));
+
+        // The assets are stored, a failed notification is only reported
+        if !cfg.notification {
+            match send_notification(
+                &cfg.webhooks,
+                format!(
+                    "## {}\n{}{}",
+                    program.to_string_lossy(),
+                    if apexes.is_empty() {
+                        String::new()
+                    } else {
+                        format!("### New apex: {}\n", apexes.join(", "))
+                    },
+                    args.iter().map(|str| str.to_string_lossy()).join("\n"),
+                ),
+            ) {
+                Ok(res) if cfg.verbosity && !cfg.webhooks.is_empty() => {
+                    eprintln!(
+                        "Notification sent to {} webhooks: {res}",
+                        cfg.webhooks.len()
+                    )
+                }
+                Ok(_) => (),
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+
+        append_res?;
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use cidr_utils::cidr::IpCidr;
+use std::{fmt::Display, net::IpAddr, str::FromStr};
+
+/// Inclusive range of addresses of one family, parsed from an IP, a CIDR or `first-last`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    pub first: IpAddr,
+    pub last: IpAddr,
+}
+
+impl IpRange {
+    pub fn contains(&self, other: &IpRange) -> bool {
+        same_family(self.first, other.first) && self.first <= other.first && other.last <= self.last
+    }
+
+    pub fn intersects(&self, other: &IpRange) -> bool {
+        same_family(self.first, other.first) && self.first <= other.last && other.first <= self.last
+    }
+}
+
+fn same_family(a: IpAddr, b: IpAddr) -> bool {
+    a.is_ipv4() == b.is_ipv4()
+}
+
+impl From<&IpCidr> for IpRange {
+    fn from(cidr: &IpCidr) -> Self {
+        Self {
+            first: cidr.first_as_ip_addr(),
+            last: cidr.last_as_ip_addr(),
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((first, last)) = s.split_once('-') {
+            let first = IpAddr::from_str(first.trim()).map_err(|e| format!("{s:?}: {e}"))?;
+            let last = IpAddr::from_str(last.trim()).map_err(|e| format!("{s:?}: {e}"))?;
+            if !same_family(first, last) || first > last {
+                return Err(format!("{s:?} is not a valid IP range"));
+            }
+            Ok(Self { first, last })
+        } else {
+            IpCidr::from_str(s)
+                .map(|cidr| Self::from(&cidr))
+                .map_err(|_| format!("{s:?} is not an IP, CIDR or IP range"))
+        }
+    }
+}
+
+impl Display for IpRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+/// How a stored range must relate to the searched one to match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// Stored range contains the query
+    Contains,
+    /// Stored range is inside the query
+    Within,
+    /// Ranges share at least one address
+    #[default]
+    Intersects,
+}
+
+impl Overlap {
+    pub fn matches(&self, stored: &IpRange, query: &IpRange) -> bool {
+        match self {
+            Overlap::Contains => stored.contains(query),
+            Overlap::Within => query.contains(stored),
+            Overlap::Intersects => stored.intersects(query),
+        }
+    }
+}
+
+impl FromStr for Overlap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contains" => Ok(Self::Contains),
+            "within" => Ok(Self::Within),
+            "intersects" => Ok(Self::Intersects),
+            _ => Err(format!("{s:?} is not one of contains, within, intersects")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(s: &str) -> IpRange {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn overlap() {
+        let stored = r("10.1.2.0/24");
+
+        assert!(Overlap::Within.matches(&stored, &r("10.0.0.0/8")));
+        assert!(!Overlap::Contains.matches(&stored, &r("10.0.0.0/8")));
+        assert!(Overlap::Contains.matches(&stored, &r("10.1.2.3")));
+        assert!(Overlap::Intersects.matches(&stored, &r("10.1.2.250-10.1.3.4")));
+        assert!(!Overlap::Intersects.matches(&stored, &r("10.1.3.0-10.1.3.4")));
+        assert!(!Overlap::Intersects.matches(&stored, &r("::/0")));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(r("1.2.3.4-1.2.3.99").to_string(), "1.2.3.4-1.2.3.99");
+        assert_eq!(r("1.2.3.4").to_string(), "1.2.3.4");
+        assert!("1.2.3.99-1.2.3.4".parse::<IpRange>().is_err());
+        assert!("example.com".parse::<IpRange>().is_err());
+    }
+}
@@ -0,0 +1,674 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::OsStr,
+    fmt::Display,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    args::Args,
+    error::WithPath,
+    utils::{self, Input, Memfind},
+    Result,
+};
+
+/// Version of the log line format, written as `v` on each line.
+pub const VERSION: u64 = 1;
+
+/// Marks the entries of an undone batch in the old format.
+const REVERTED: &str = "reverted";
+
+/// `hust migrate`, and `hust log`: the entries of the logs of `-p` or all programs,
+/// merged in chronological order.
+pub fn run(cfg: &Args) -> Result<()> {
+    if cfg.args.first().is_some_and(|cmd| cmd == "migrate") {
+        let migrated = migrate(&all(&cfg.path)?)?;
+        println!("{migrated} log lines migrated to version {VERSION}");
+        return Ok(());
+    }
+
+    let program = cfg.program.as_ref().map(|p| p.to_string_lossy());
+    let mut entries = Vec::new();
+    each(&cfg.log.read(&cfg.path, cfg.program.as_deref())?, |entry| {
+        if program.as_ref().is_none_or(|p| entry.program == *p)
+            && cfg.since.is_none_or(|since| entry.date >= since)
+            && (cfg.verbosity || !entry.reverted)
+        {
+            entries.push(entry.into_owned());
+        }
+    })?;
+    entries.sort_by_key(|entry| entry.date);
+
+    let mut stdout = std::io::stdout().lock();
+    for entry in entries {
+        if cfg.json {
+            writeln!(stdout, "{entry}")?;
+            continue;
+        }
+        let date = entry
+            .date
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+        write!(stdout, "{date} | {} | {}", entry.program, entry.asset)?;
+        if cfg.verbosity {
+            write!(stdout, " | {}", entry.batch.as_deref().unwrap_or("-"))?;
+            if entry.reverted {
+                write!(stdout, " | reverted")?;
+            }
+        }
+        writeln!(stdout)?;
+    }
+
+    Ok(())
+}
+
+/// A line of `hust.log`, a JSON object since version 1:
+/// `{"v":1,"program":"x","asset":"a.example.com","date":"2026-10-19T07:50:50Z","batch":"..."}`.
+///
+/// Older lines are `program | asset | RFC 2822 date [| batch] [| reverted]` and still read.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub program: Cow<'a, str>,
+    pub asset: Cow<'a, str>,
+    pub date: DateTime<FixedOffset>,
+    /// Id of the insert that logged it
+    pub batch: Option<Cow<'a, str>>,
+    /// Removed from the stores by `undo`
+    pub reverted: bool,
+}
+
+impl<'a> Entry<'a> {
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.starts_with('{') {
+            Self::parse_json(line)
+        } else {
+            Self::parse_v0(line)
+        }
+    }
+
+    fn parse_json(line: &str) -> Option<Self> {
+        let Value::Object(json) = serde_json::from_str(line).ok()? else {
+            return None;
+        };
+        let str = |key: &str| json.get(key)?.as_str().map(|s| Cow::Owned(s.to_string()));
+        if json.get("v")?.as_u64()? > VERSION {
+            return None;
+        }
+        Some(Self {
+            program: str("program")?,
+            asset: str("asset")?,
+            date: DateTime::parse_from_rfc3339(json.get("date")?.as_str()?).ok()?,
+            batch: str("batch"),
+            reverted: json.get("reverted").and_then(Value::as_bool) == Some(true),
+        })
+    }
+
+    fn parse_v0(line: &'a str) -> Option<Self> {
+        let (program, mut rest) = line.split_once(" | ")?;
+        let mut reverted = false;
+        if let Some(head) = rest
+            .strip_suffix(REVERTED)
+            .and_then(|r| r.strip_suffix(" | "))
+        {
+            rest = head;
+            reverted = true;
+        }
+
+        let (head, last) = rest.rsplit_once(" | ")?;
+        if let Ok(date) = DateTime::parse_from_rfc2822(last.trim()) {
+            return Some(Self {
+                program: program.into(),
+                asset: head.into(),
+                date,
+                batch: None,
+                reverted,
+            });
+        }
+        let (asset, date) = head.rsplit_once(" | ")?;
+        Some(Self {
+            program: program.into(),
+            asset: asset.into(),
+            date: DateTime::parse_from_rfc2822(date.trim()).ok()?,
+            batch: Some(last.trim().into()),
+            reverted,
+        })
+    }
+}
+
+impl Entry<'_> {
+    pub fn into_owned(self) -> Entry<'static> {
+        Entry {
+            program: self.program.into_owned().into(),
+            asset: self.asset.into_owned().into(),
+            date: self.date,
+            batch: self.batch.map(|batch| batch.into_owned().into()),
+            reverted: self.reverted,
+        }
+    }
+}
+
+/// The current format, one JSON object without a newline.
+impl Display for Entry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = |s: &str| Value::from(s).to_string();
+        write!(
+            f,
+            r#"{{"v":{VERSION},"program":{},"asset":{},"date":{}"#,
+            json(&self.program),
+            json(&self.asset),
+            json(
+                &self
+                    .date
+                    .with_timezone(&Utc)
+                    .to_rfc3339_opts(SecondsFormat::Secs, true)
+            )
+        )?;
+        if let Some(batch) = &self.batch {
+            write!(f, r#","batch":{}"#, json(batch))?;
+        }
+        if self.reverted {
+            write!(f, r#","reverted":true"#)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Where `insert` logs new assets: `hust.log`, `<program>/log` or both.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    #[default]
+    Hunt,
+    Program,
+    Both,
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "hunt" => Ok(Self::Hunt),
+            "program" => Ok(Self::Program),
+            "both" => Ok(Self::Both),
+            _ => Err(format!("{s:?} is not one of hunt, program, both")),
+        }
+    }
+}
+
+impl Target {
+    /// Logs written by an insert into `program`.
+    pub fn write(self, path: &Path, program: &OsStr) -> Vec<PathBuf> {
+        let (hunt, program) = (path.join("hust.log"), path.join(program).join("log"));
+        match self {
+            Self::Hunt => vec![hunt],
+            Self::Program => vec![program],
+            Self::Both => vec![hunt, program],
+        }
+    }
+
+    /// Logs holding the entries of `program`, or of all programs, without duplicates.
+    pub fn read(self, path: &Path, program: Option<&OsStr>) -> Result<Vec<PathBuf>> {
+        Ok(match (self, program) {
+            (Self::Hunt | Self::Both, _) => vec![path.join("hust.log")],
+            (Self::Program, Some(program)) => vec![path.join(program).join("log")],
+            (Self::Program, None) => all(path)?
+                .into_iter()
+                .filter(|log| log != &path.join("hust.log"))
+                .collect(),
+        })
+    }
+}
+
+/// `hust.log` and the log of every program.
+pub fn all(path: &Path) -> Result<Vec<PathBuf>> {
+    Ok(Some(path.join("hust.log"))
+        .into_iter()
+        .chain(
+            utils::programs(path)?
+                .into_iter()
+                .map(|program| program.path().join("log")),
+        )
+        .collect())
+}
+
+/// Size and age after which `hust.log` is rotated.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rotation {
+    /// Bytes
+    pub max_size: Option<u64>,
+    /// Age of the first entry
+    pub max_age: Option<Duration>,
+}
+
+/// `512K`, `10M`, `1G` or bytes.
+pub fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let err = || format!("{s:?} is not a size, use 512K, 10M, 1G or bytes");
+    let (n, unit) = match s.trim().char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&s.trim()[..i], c.to_ascii_uppercase()),
+        _ => (s.trim(), 'B'),
+    };
+    let n: u64 = n.trim().parse().map_err(|_| err())?;
+    let unit: u64 = match unit {
+        'B' => 1,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        _ => return Err(err()),
+    };
+    n.checked_mul(unit).ok_or_else(err)
+}
+
+/// `30s`, `5m`, `12h`, `7d`, `2w`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (i, unit) = s.char_indices().last()?;
+    let n: i64 = s[..i].parse().ok()?;
+    match unit {
+        's' => Duration::try_seconds(n),
+        'm' => Duration::try_minutes(n),
+        'h' => Duration::try_hours(n),
+        'd' => Duration::try_days(n),
+        'w' => Duration::try_weeks(n),
+        _ => None,
+    }
+}
+
+/// Rotated segments of the log, `hust.log.<UTC timestamp>.gz`, oldest first.
+pub fn segments(path: &Path) -> Result<Vec<PathBuf>> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut segments = fs::read_dir(dir)
+        .with_path(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".gz"))
+        })
+        .collect::<Vec<_>>();
+    segments.sort();
+    Ok(segments)
+}
+
+/// Segments then the current file of each log, oldest first.
+fn files(logs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for log in logs {
+        files.extend(segments(log)?);
+        if log.is_file() {
+            files.push(log.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn read(file: &Path) -> Result<String> {
+    if file.extension().is_some_and(|ext| ext == "gz") {
+        let mut content = String::new();
+        GzDecoder::new(File::open(file).with_path(file)?)
+            .read_to_string(&mut content)
+            .with_path(file)?;
+        Ok(content)
+    } else {
+        fs::read_to_string(file).with_path(file)
+    }
+}
+
+fn write(file: &Path, content: &str) -> Result<()> {
+    if file.extension().is_some_and(|ext| ext == "gz") {
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(content.as_bytes()).with_path(file)?;
+        utils::replace(file, &gz.finish().with_path(file)?)
+    } else {
+        utils::replace(file, content.as_bytes())
+    }
+}
+
+/// Every entry of the logs and their rotated segments, oldest first in each log.
+pub fn each(logs: &[PathBuf], mut f: impl FnMut(Entry)) -> Result<()> {
+    for file in files(logs)? {
+        if logs.contains(&file) {
+            // Can be huge, mapped instead of read
+            for line in Input::open(&file)?.find(&[]) {
+                if let Some(entry) = Entry::parse(&String::from_utf8_lossy(line)) {
+                    f(entry);
+                }
+            }
+        } else {
+            read(&file)?
+                .lines()
+                .filter_map(Entry::parse)
+                .for_each(&mut f);
+        }
+    }
+    Ok(())
+}
+
+/// Replace the entries `f` returns a line for, in the logs and their segments.
+///
+/// Return how many were replaced, changed files are kept as `<file>.bak` if `backup`.
+fn rewrite(
+    logs: &[PathBuf],
+    backup: bool,
+    mut f: impl FnMut(&str, Entry) -> Option<String>,
+) -> Result<usize> {
+    let mut replaced = 0;
+    for file in files(logs)? {
+        let content = read(&file)?;
+        let mut changed = false;
+        let content = content
+            .lines()
+            .map(
+                |line| match Entry::parse(line).and_then(|entry| f(line, entry)) {
+                    Some(line) => {
+                        changed = true;
+                        replaced += 1;
+                        format!("{line}\n")
+                    }
+                    None => format!("{line}\n"),
+                },
+            )
+            .collect::<String>();
+
+        if changed {
+            if backup {
+                let mut bak = file.clone().into_os_string();
+                bak.push(".bak");
+                fs::copy(&file, &bak).with_path(&bak)?;
+            }
+            write(&file, &content)?;
+        }
+    }
+    Ok(replaced)
+}
+
+/// Compress the log into a segment if it's bigger or older than `rotation` allows.
+pub fn rotate(path: &Path, rotation: Rotation, now: DateTime<Utc>) -> Result<Option<PathBuf>> {
+    let Ok(meta) = fs::metadata(path) else {
+        return Ok(None);
+    };
+    if meta.len() == 0 {
+        return Ok(None);
+    }
+
+    let too_big = rotation.max_size.is_some_and(|max| meta.len() >= max);
+    let too_old = || {
+        let Some(max) = rotation.max_age else {
+            return Ok(false);
+        };
+        let first = Input::open(path)?
+            .find(&[])
+            .into_iter()
+            .find_map(|line| Some(Entry::parse(&String::from_utf8_lossy(line))?.date));
+        Ok::<_, crate::Error>(first.is_some_and(|date| now.signed_duration_since(date) >= max))
+    };
+    if !too_big && !too_old()? {
+        return Ok(None);
+    }
+
+    let mut segment = path.to_path_buf().into_os_string();
+    segment.push(format!(".{}.gz", now.format("%Y%m%d%H%M%S")));
+    let segment = PathBuf::from(segment);
+    // Already rotated this second
+    if segment.exists() {
+        return Ok(None);
+    }
+
+    // Moved away before it's read, lines appended meanwhile go to a new log
+    let plain = segment.with_extension("");
+    fs::rename(path, &plain).with_path(path)?;
+    write(&segment, &read(&plain)?)?;
+    fs::remove_file(&plain).with_path(&plain)?;
+    Ok(Some(segment))
+}
+
+/// Rewrite the lines of older formats in the current one, return how many.
+///
+/// The originals are kept as `<file>.bak`.
+pub fn migrate(logs: &[PathBuf]) -> Result<usize> {
+    rewrite(logs, true, |line, entry| {
+        (!line.starts_with('{')).then(|| entry.to_string())
+    })
+}
+
+/// Id of an insert, written on each of its log lines.
+pub fn batch_id() -> String {
+    format!(
+        "{}-{}",
+        Local::now().format("%Y%m%d%H%M%S"),
+        std::process::id()
+    )
+}
+
+/// Batch of the last entry that isn't reverted.
+pub fn last_batch(logs: &[PathBuf]) -> Result<Option<String>> {
+    let mut last = None;
+    each(logs, |entry| match (&last, entry.batch) {
+        (Some((date, _)), _) if entry.date < *date => (),
+        (_, Some(batch)) if !entry.reverted => last = Some((entry.date, batch.to_string())),
+        _ => (),
+    })?;
+    Ok(last.map(|(_, batch)| batch))
+}
+
+/// `(program, asset)` of the entries of `batch` that aren't reverted.
+pub fn batch(logs: &[PathBuf], batch: &str) -> Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+    each(logs, |entry| {
+        if entry.batch.as_deref() == Some(batch) && !entry.reverted {
+            entries.push((entry.program.to_string(), entry.asset.to_string()));
+        }
+    })?;
+    Ok(entries)
+}
+
+/// Mark the entries of `batch` as reverted.
+pub fn revert(logs: &[PathBuf], batch: &str) -> Result<()> {
+    rewrite(logs, false, |_, mut entry| {
+        (entry.batch.as_deref() == Some(batch) && !entry.reverted).then(|| {
+            entry.reverted = true;
+            entry.to_string()
+        })
+    })?;
+    Ok(())
+}
+
+/// Move the entries of program `from` to `to`.
+pub fn rename(logs: &[PathBuf], from: &str, to: &str) -> Result<()> {
+    rewrite(logs, false, |_, mut entry| {
+        (entry.program == from).then(|| {
+            entry.program = to.to_string().into();
+            entry.to_string()
+        })
+    })?;
+    Ok(())
+}
+
+/// `--since`: RFC 3339, RFC 2822, `2026-10-12` (local midnight) or relative `12h`, `7d`, `2w`.
+pub fn parse_since(s: &str) -> std::result::Result<DateTime<FixedOffset>, String> {
+    let err = || format!("{s:?} is not a date, use 2026-10-12, RFC 3339 or 12h, 7d, 2w");
+    if let Ok(date) = DateTime::parse_from_rfc3339(s).or_else(|_| DateTime::parse_from_rfc2822(s)) {
+        return Ok(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).ok_or_else(err)?)
+            .earliest()
+            .map(|date| date.fixed_offset())
+            .ok_or_else(err);
+    }
+    let ago = parse_duration(s).ok_or_else(err)?;
+    Ok((Local::now() - ago).fixed_offset())
+}
+
+/// First time each asset of `program` was logged.
+pub fn first_seen(
+    logs: &[PathBuf],
+    program: &OsStr,
+) -> Result<HashMap<String, DateTime<FixedOffset>>> {
+    let mut seen = HashMap::new();
+    let program = program.to_string_lossy();
+    each(logs, |entry| {
+        if entry.program == program && !entry.reverted {
+            seen.entry(entry.asset.to_string())
+                .and_modify(|date: &mut DateTime<FixedOffset>| *date = entry.date.min(*date))
+                .or_insert(entry.date);
+        }
+    })?;
+    Ok(seen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::tests::test_dir;
+
+    #[test]
+    fn parse() {
+        let entry = Entry::parse("x | a.example.com | Mon, 19 Oct 2026 07:50:50 +0000").unwrap();
+
+        assert_eq!(entry.program, "x");
+        assert_eq!(entry.asset, "a.example.com");
+        assert_eq!(entry.date.to_rfc3339(), "2026-10-19T07:50:50+00:00");
+        assert!(Entry::parse("garbage").is_none());
+
+        let line = "x | a | b.com | Mon, 19 Oct 2026 07:50:50 +0000 | 20261019075050-1 | reverted";
+        let entry = Entry::parse(line).unwrap();
+        assert_eq!(entry.asset, "a | b.com");
+        assert_eq!(entry.batch.as_deref(), Some("20261019075050-1"));
+        assert!(entry.reverted);
+
+        let json = entry.to_string();
+        assert_eq!(
+            json,
+            r#"{"v":1,"program":"x","asset":"a | b.com","date":"2026-10-19T07:50:50Z","batch":"20261019075050-1","reverted":true}"#
+        );
+        assert_eq!(Entry::parse(&json).unwrap(), entry);
+        assert!(Entry::parse(r#"{"v":2,"program":"x"}"#).is_none());
+    }
+
+    #[test]
+    fn migrate_v0() {
+        let path = test_dir("migrate").join("hust.log");
+        let logs = [path.clone()];
+        fs::write(
+            &path,
+            "x | a.com | Mon, 19 Oct 2026 09:50:50 +0200\n\
+             {\"v\":1,\"program\":\"x\",\"asset\":\"b.com\",\"date\":\"2026-10-19T08:00:00Z\"}\n",
+        )
+        .unwrap();
+
+        assert_eq!(migrate(&logs).unwrap(), 1);
+        assert_eq!(migrate(&logs).unwrap(), 0);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().lines().next(),
+            Some(r#"{"v":1,"program":"x","asset":"a.com","date":"2026-10-19T07:50:50Z"}"#)
+        );
+        assert!(fs::read_to_string(path.with_extension("log.bak"))
+            .unwrap()
+            .starts_with("x | a.com"));
+    }
+
+    #[test]
+    fn undo_batch() {
+        let path = test_dir("batch").join("hust.log");
+        let logs = [path.clone()];
+        fs::write(
+            &path,
+            "x | old.com | Mon, 19 Oct 2026 07:50:50 +0000\n\
+             x | a.com | Mon, 19 Oct 2026 07:50:50 +0000 | 1\n\
+             y | b.com | Mon, 19 Oct 2026 07:50:50 +0000 | 1\n\
+             x | c.com | Mon, 19 Oct 2026 07:50:50 +0000 | 2\n",
+        )
+        .unwrap();
+
+        assert_eq!(last_batch(&logs).unwrap().as_deref(), Some("2"));
+        revert(&logs, "2").unwrap();
+        assert_eq!(last_batch(&logs).unwrap().as_deref(), Some("1"));
+        assert_eq!(
+            batch(&logs, "1").unwrap(),
+            [
+                ("x".to_string(), "a.com".to_string()),
+                ("y".to_string(), "b.com".to_string())
+            ]
+        );
+        assert!(batch(&logs, "2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rotate_and_read_segments() {
+        let dir = test_dir("rotate");
+        let path = dir.join("hust.log");
+        let logs = [path.clone()];
+        fs::write(
+            &path,
+            "x | a.com | Mon, 05 Oct 2026 07:00:00 +0000 | 1\n\
+             x | b.com | Mon, 19 Oct 2026 07:00:00 +0000 | 2\n",
+        )
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        let age = Rotation {
+            max_size: None,
+            max_age: parse_duration("30d"),
+        };
+        assert!(rotate(&path, age, now).unwrap().is_none());
+        let size = Rotation {
+            max_size: Some(parse_size("64").unwrap()),
+            max_age: None,
+        };
+        let segment = rotate(&path, size, now).unwrap().unwrap();
+        assert_eq!(segment.file_name().unwrap(), "hust.log.20261019080000.gz");
+        assert!(!path.exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        utils::append(
+            path.clone(),
+            "x | c.com | Mon, 19 Oct 2026 08:00:00 +0000 | 3",
+        )
+        .unwrap();
+
+        assert_eq!(first_seen(&logs, "x".as_ref()).unwrap().len(), 3);
+        revert(&logs, "1").unwrap();
+        assert_eq!(first_seen(&logs, "x".as_ref()).unwrap().len(), 2);
+        assert_eq!(last_batch(&logs).unwrap().as_deref(), Some("3"));
+        assert_eq!(parse_size("10M"), Ok(10 << 20));
+        assert!(parse_size("99999999999G").is_err());
+        assert_eq!(parse_duration(" 2w "), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("5é"), None);
+        assert_eq!(parse_duration("é"), None);
+    }
+
+    #[test]
+    fn since() {
+        assert_eq!(
+            parse_since("2026-10-12T00:00:00Z").unwrap().to_rfc3339(),
+            "2026-10-12T00:00:00+00:00"
+        );
+        assert!(parse_since("2026-10-12").is_ok());
+        let week = Local::now().fixed_offset() - parse_since("7d").unwrap();
+        assert!((week - Duration::days(7)).num_seconds().abs() < 5);
+        assert!(parse_since("last monday").is_err());
+    }
+}
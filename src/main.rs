@@ -1,202 +1,69 @@
 mod args;
+mod asn;
+mod cloud;
 mod database;
+mod diff;
+mod error;
+mod export;
+mod hook;
+mod insert;
+mod ip;
+mod log;
 mod notification;
+mod programs;
+mod psl;
+mod search;
+mod snapshot;
+mod status;
+mod tools;
+mod tree;
+mod undo;
 mod utils;
-use cidr_utils::cidr::IpCidr;
-use utils::Memfind;
+mod watch;
+mod words;
 
-use database::DataBase as db;
+use args::Args;
 
-use args::{Args, Webhook};
-use itertools::Itertools;
-use memmap2::MmapOptions;
-use notification::send_notification;
+use std::os::unix::ffi::OsStrExt;
 
-use std::{
-    error::Error,
-    ffi::OsString,
-    fs::{self, read_dir, File},
-    io::Write,
-    os::unix::ffi::OsStrExt,
-    path::{Path, PathBuf},
-};
-
-type Result<T> = std::result::Result<T, Box<dyn Error>>;
+use error::{Error, Result, WithPath};
 
 fn main() {
-    let cfg = Args::parse().unwrap();
-
-    if let Err(err) = run(cfg) {
+    if let Err(err) = Args::parse().and_then(run) {
         eprintln!("{err}");
-        std::process::exit(2);
+        std::process::exit(err.exit_code());
     }
 }
 
-fn run(args: Args) -> Result<()> {
-    match args.args.split_first() {
-        Some((first, rest)) => match first.as_bytes() {
-            b"domain" | b"ip" => search(&args.path, &args.program, first, rest, args.verbosity),
-            b"log" => todo!(), //TODO
-            _ => {
-                match args.program {
-                    Some(program) => insert(
-                        args.path,
-                        program,
-                        args.args,
-                        args.notification,
-                        args.webhooks,
-                    )?,
-                    None => return Err("Program (-p) must be specified!".into()),
-                };
-                Ok(())
+fn run(mut args: Args) -> Result<()> {
+    match args.args.first().map(|first| first.as_bytes()) {
+        Some(b"domain" | b"ip") => {
+            if args.piped {
+                for token in args.insert.split.read(std::io::stdin().lock()) {
+                    args.args.push(token.with_path("stdin")?);
+                }
             }
-        },
-        None => status(args),
-    }
-}
-
-const BANNER: &str = "
-    ██░ ██  █    ██   ██████ ▄▄▄█████▓
-    ▓██░ ██▒ ██  ▓██▒▒██    ▒ ▓  ██▒ ▓▒
-    ▒██▀▀██░▓██  ▒██░░ ▓██▄   ▒ ▓██░ ▒░
-    ░▓█ ░██ ▓▓█  ░██░  ▒   ██▒░ ▓██▓ ░ 
-    ░▓█▒░██▓▒▒█████▓ ▒██████▒▒  ▒██▒ ░ 
-    ▒ ░░▒░▒░▒▓▒ ▒ ▒ ▒ ▒▓▒ ▒ ░  ▒ ░░   
-    ▒ ░▒░ ░░░▒░ ░ ░ ░ ░▒  ░ ░    ░    
-    ░  ░░ ░ ░░░ ░ ░ ░  ░  ░    ░      
-    ░  ░  ░   ░           ░            
-";
-fn status(cfg: Args) -> Result<()> {
-    let mut stdout = std::io::stdout();
-    write!(
-        stdout,
-        "{}\n        Taste That PINK VENOM!    \n\nHunt Path: {}\n\nWebhooks: {}\n\nConfig file: {}",
-        BANNER,
-        &cfg.path.to_string_lossy(),
-        cfg.webhooks
-            .iter()
-            .join("             \n"),
-        args::get_config_file()?.0.to_string_lossy()
-    )?;
-
-    Ok(())
-}
-
-fn insert(
-    path: PathBuf,
-    program: OsString,
-    args: Vec<OsString>,
-    notification: bool,
-    webhooks: Vec<Webhook>,
-) -> Result<()> {
-    let mut db = db::init(&path, &program)?.import(args, true);
-
-    db.write()?;
-
-    let args = db.new.1;
-    if !args.is_empty() {
-        let append_res = utils::append(
-            path.join("hust.log"),
-            &args
-                .iter()
-                .map(|str| {
-                    format!(
-                        "{} | {} | {}",
-                        program.to_string_lossy(),
-                        str.to_string_lossy(),
-                        chrono::Local::now().to_rfc2822()
-                    )
-                })
-                .join("\n"),
-        );
-
-        if !notification {
-            send_notification(
-                webhooks,
-                format!(
-                    "## {}\n{}",
-                    program.to_string_lossy(),
-                    args.iter().map(|str| str.to_string_lossy()).join("\n"),
-                ),
-            )?;
+            let (first, rest) = args.args.split_first().unwrap();
+            search::run(&args, first, rest)
         }
-
-        append_res?;
-    }
-    Ok(())
-}
-
-fn search(
-    path: &Path,
-    program: &Option<OsString>,
-    first: &OsString,
-    args: &[OsString],
-    v: bool,
-) -> Result<()> {
-    fs::read_dir(path)?
-        .flatten()
-        .filter(|e| {
-            e.path().is_dir()
-                && match (program, e.path().file_name()) {
-                    (Some(program), Some(path)) => program == path,
-                    _ => true,
-                }
-        })
-        .for_each(|program| {
-            if let Ok(e) = read_dir(program.path()) {
-                let iter = e.flatten().filter(|e| &e.file_name() == first);
-                if first == "domain" {
-                    iter.flat_map(|e| File::open(e.path())).for_each(|f| {
-                        let mmap = unsafe { MmapOptions::new().map(&f).unwrap() };
-
-                        for arg in mmap.find(args) {
-                            if v {
-                                println!(
-                                    "{} | {}",
-                                    program.file_name().to_string_lossy(),
-                                    String::from_utf8_lossy(arg)
-                                );
-                            } else {
-                                println!("{}", String::from_utf8_lossy(arg));
-                            }
-                        }
-                    });
-                } else {
-                    // Search in CIDRs
-                    iter.flat_map(|e| File::open(e.path())).for_each(|f| {
-                        let mmap = unsafe { MmapOptions::new().map(&f).unwrap() };
-
-                        let args = args
-                            .iter()
-                            .filter_map(|arg| IpCidr::from_str(arg.to_string_lossy()).ok())
-                            .collect_vec();
-
-                        for line in mmap.find(&[]) {
-                            if let Ok(cidr) =
-                                IpCidr::from_str(unsafe { std::str::from_utf8_unchecked(line) })
-                            {
-                                for arg in args.iter() {
-                                    if cidr.contains(arg.first_as_ip_addr())
-                                        || cidr.contains(arg.last_as_ip_addr())
-                                    {
-                                        if v {
-                                            println!(
-                                                "{} | {} | {}",
-                                                program.file_name().to_string_lossy(),
-                                                cidr,
-                                                arg
-                                            );
-                                        } else {
-                                            println!("{}", cidr);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    });
-                }
+        Some(b"tree") => tree::run(&args, &args.args[1..]),
+        Some(b"words") => words::run(&args),
+        Some(b"export") => export::run(&args),
+        Some(b"diff") => diff::run(&args),
+        Some(b"snapshot" | b"snapshots" | b"restore") => snapshot::run(&args),
+        Some(b"undo") => undo::run(&args),
+        Some(b"log" | b"migrate") => log::run(&args),
+        Some(b"programs") => programs::run(&args, &args.args[1..]),
+        Some(b"watch") => watch::run(&args),
+        _ if args.inserts() => {
+            let program = args.program()?.clone();
+            if args.insert.create {
+                programs::check_name(&program)?;
+            } else {
+                programs::check_exists(&args.path, &program)?;
             }
-        });
-
-    Ok(())
+            insert::run(args, program)
+        }
+        _ => status::run(&args),
+    }
 }
@@ -0,0 +1,227 @@
+use chrono::{DateTime, Local};
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    io::{IsTerminal, Write},
+    path::Path,
+};
+
+use crate::{
+    args::Args,
+    error::{Error, WithPath},
+    utils::{self, Input, Memfind},
+    Result,
+};
+
+/// Archived programs are moved here, hidden from the other commands.
+pub const ARCHIVE: &str = ".archive";
+
+pub fn run(cfg: &Args, args: &[OsString]) -> Result<()> {
+    match args.split_first() {
+        None => list(&cfg.path),
+        Some((cmd, rest)) => match (cmd.to_string_lossy().as_ref(), rest) {
+            ("create", [name]) => create(&cfg.path, name),
+            ("rename", [from, to]) => rename(&cfg.path, from, to),
+            ("archive", [name]) => archive(&cfg.path, name),
+            ("delete", [name]) => delete(&cfg.path, name, cfg.yes),
+            _ => Err(Error::Config(
+                "Usage: hust programs [create <name> | rename <from> <to> | archive <name> | delete <name>]"
+                    .into(),
+            )),
+        },
+    }
+}
+
+/// Programs selected by `-p`, or all of them.
+pub fn selected(cfg: &Args) -> Result<Vec<fs::DirEntry>> {
+    let programs = utils::programs(&cfg.path)?;
+    match &cfg.program {
+        Some(program) => {
+            let programs = programs
+                .into_iter()
+                .filter(|e| &e.file_name() == program)
+                .collect::<Vec<_>>();
+            if programs.is_empty() {
+                return Err(Error::Config(format!(
+                    "Program {:?} doesn't exist",
+                    program
+                )));
+            }
+            Ok(programs)
+        }
+        None => Ok(programs),
+    }
+}
+
+/// Programs with per-kind counts and last activity.
+fn list(path: &Path) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(
+        stdout,
+        "{:<24} {:>8} {:>8} {:>8}  last activity",
+        "program", "ip", "domain", "other"
+    )?;
+
+    for program in utils::programs(path)? {
+        let dir = program.path();
+        let count = |store: &str| {
+            Input::open(dir.join(store))
+                .map(|input| input.find(&[]).len())
+                .unwrap_or_default()
+        };
+        let last = fs::read_dir(&dir)
+            .with_path(&dir)?
+            .flatten()
+            .filter_map(|e| e.metadata().ok()?.modified().ok())
+            .max()
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        writeln!(
+            stdout,
+            "{:<24} {:>8} {:>8} {:>8}  {}",
+            program.file_name().to_string_lossy(),
+            count("ip"),
+            count("domain"),
+            count("other"),
+            last
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Program names are plain directory names that other commands don't reserve.
+///
+/// Hidden names are for hust, e.g. `.snapshots`, and files of the hunt path or
+/// commands can't be programs.
+pub fn check_name(name: &OsStr) -> Result<()> {
+    let str = name.to_string_lossy();
+    if str.is_empty()
+        || str.starts_with('.')
+        || str.contains('/')
+        || str.starts_with("hust.log")
+        || str == "hooks.log"
+        || str == crate::cloud::DIR
+        || crate::asn::FILES.contains(&str.as_ref())
+        || crate::psl::FILES.contains(&str.as_ref())
+        || crate::args::COMMANDS.contains(&str.as_ref())
+    {
+        return Err(Error::Config(format!(
+            "{name:?} is not a valid program name"
+        )));
+    }
+    Ok(())
+}
+
+/// Error unless `name` is an existing program.
+pub fn check_exists(path: &Path, name: &OsStr) -> Result<()> {
+    check_name(name)?;
+    if !path.join(name).is_dir() {
+        return Err(Error::Config(format!(
+            "Program {name:?} doesn't exist, use --create or `hust programs create`"
+        )));
+    }
+    Ok(())
+}
+
+pub fn create(path: &Path, name: &OsStr) -> Result<()> {
+    check_name(name)?;
+    let dir = path.join(name);
+    if dir.exists() {
+        return Err(Error::Config(format!("Program {name:?} already exists")));
+    }
+    fs::create_dir_all(&dir).with_path(&dir)
+}
+
+fn rename(path: &Path, from: &OsStr, to: &OsStr) -> Result<()> {
+    check_exists(path, from)?;
+    check_name(to)?;
+    if path.join(to).exists() {
+        return Err(Error::Config(format!("Program {to:?} already exists")));
+    }
+    // Snapshots first, so nothing is renamed if they can't be
+    crate::snapshot::rename(path, from, to)?;
+    fs::rename(path.join(from), path.join(to)).with_path(path.join(from))?;
+
+    // Rewrite the program of its log lines
+    crate::log::rename(
+        &crate::log::all(path)?,
+        &from.to_string_lossy(),
+        &to.to_string_lossy(),
+    )?;
+
+    Ok(())
+}
+
+fn archive(path: &Path, name: &OsStr) -> Result<()> {
+    check_exists(path, name)?;
+    let archive = path.join(ARCHIVE);
+    fs::create_dir_all(&archive).with_path(&archive)?;
+
+    let to = archive.join(format!(
+        "{}.{}",
+        name.to_string_lossy(),
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::rename(path.join(name), &to).with_path(&to)
+}
+
+fn delete(path: &Path, name: &OsStr, yes: bool) -> Result<()> {
+    check_exists(path, name)?;
+    if !yes && !confirm(&format!("Delete program {name:?} and all of its assets?"))? {
+        return Err(Error::Config("Not deleted, confirm or use --yes".into()));
+    }
+    fs::remove_dir_all(path.join(name)).with_path(path.join(name))
+}
+
+/// Ask on the terminal, `false` if there is none.
+pub fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{question} [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_rewrites_log() {
+        let path = utils::tests::test_dir("programs");
+        fs::write(
+            path.join("hust.log"),
+            "x | a.com | Mon, 19 Oct 2026 07:50:50 +0000\nxy | b.com | Mon, 19 Oct 2026 07:50:50 +0000",
+        )
+        .unwrap();
+
+        create(&path, "x".as_ref()).unwrap();
+        assert!(create(&path, "x".as_ref()).is_err());
+        assert!(create(&path, ".archive".as_ref()).is_err());
+        for reserved in ["hust.log", "hooks.log", "asn.tsv", "providers", "export"] {
+            assert!(check_name(reserved.as_ref()).is_err());
+        }
+        crate::snapshot::create(&path, Some("x".as_ref())).unwrap();
+        rename(&path, "x".as_ref(), "z".as_ref()).unwrap();
+
+        assert!(path.join("z").is_dir());
+        assert_eq!(
+            crate::snapshot::list(&path, Some("z".as_ref()))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("hust.log")).unwrap(),
+            "{\"v\":1,\"program\":\"z\",\"asset\":\"a.com\",\"date\":\"2026-10-19T07:50:50Z\"}\nxy | b.com | Mon, 19 Oct 2026 07:50:50 +0000\n"
+        );
+    }
+}
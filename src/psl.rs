@@ -0,0 +1,102 @@
+use std::{collections::HashSet, path::Path};
+
+use crate::{error::WithPath, Result};
+
+/// Public Suffix Lists looked up in order, the first is relative to the hunt path.
+/// Without any, the list bundled at build time is used.
+pub const FILES: [&str; 2] = [
+    "public_suffix_list.dat",
+    "/usr/share/publicsuffix/public_suffix_list.dat",
+];
+
+/// Copy of <https://publicsuffix.org/list/public_suffix_list.dat>, refresh it before releases.
+const BUNDLED: &str = include_str!("public_suffix_list.dat");
+
+#[derive(Debug, Default)]
+pub struct Psl {
+    rules: HashSet<String>,
+    /// `*.ck` is stored as `ck`
+    wildcards: HashSet<String>,
+    /// `!www.ck` is stored as `www.ck`
+    exceptions: HashSet<String>,
+}
+
+impl Psl {
+    pub fn load(path: &Path) -> Result<Self> {
+        match FILES.iter().map(|f| path.join(f)).find(|f| f.is_file()) {
+            Some(file) => Ok(Self::parse(
+                &std::fs::read_to_string(&file).with_path(&file)?,
+            )),
+            None => Ok(Self::parse(BUNDLED)),
+        }
+    }
+
+    pub fn parse(list: &str) -> Self {
+        let mut psl = Self::default();
+        for rule in list
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|rule| !rule.starts_with("//"))
+        {
+            let rule = rule.to_lowercase();
+            if let Some(rule) = rule.strip_prefix('!') {
+                psl.exceptions.insert(rule.to_string());
+            } else if let Some(rule) = rule.strip_prefix("*.") {
+                psl.wildcards.insert(rule.to_string());
+            } else {
+                psl.rules.insert(rule);
+            }
+        }
+        psl
+    }
+
+    /// Registrable domain (public suffix + one label), `None` if `domain` is a public suffix.
+    pub fn apex(&self, domain: &str) -> Option<String> {
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        let labels = domain.split('.').collect::<Vec<_>>();
+        let suffix = |i: usize| labels[i..].join(".");
+
+        // Longest matching rule wins, default is the last label
+        let ps = (0..labels.len())
+            .find_map(|i| {
+                if self.exceptions.contains(&suffix(i)) {
+                    Some(i + 1)
+                } else if self.rules.contains(&suffix(i))
+                    || (i + 1 < labels.len() && self.wildcards.contains(&suffix(i + 1)))
+                {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(labels.len() - 1);
+
+        (ps > 0).then(|| suffix(ps - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apex() {
+        let psl = Psl::parse("// comment\ncom\nuk\nco.uk\n*.ck\n!www.ck\n");
+
+        assert_eq!(psl.apex("a.b.example.com").as_deref(), Some("example.com"));
+        assert_eq!(
+            psl.apex("api.example.co.uk").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(psl.apex("a.b.foo.ck").as_deref(), Some("b.foo.ck"));
+        assert_eq!(psl.apex("a.www.ck").as_deref(), Some("www.ck"));
+        assert_eq!(psl.apex("Example.ORG.").as_deref(), Some("example.org"));
+        assert_eq!(psl.apex("co.uk"), None);
+
+        let bundled = Psl::parse(BUNDLED);
+        assert_eq!(
+            bundled.apex("a.example.github.io").as_deref(),
+            Some("example.github.io")
+        );
+    }
+}
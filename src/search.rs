@@ -0,0 +1,273 @@
+use cidr_utils::cidr::IpCidr;
+use itertools::Itertools;
+use std::{
+    collections::HashMap, ffi::OsString, fs::read_dir, hash::Hash, path::Path, str::FromStr,
+};
+
+use crate::{
+    args::{Args, Filter},
+    asn::{parse_asn, AsnDb},
+    cloud, database,
+    error::Error,
+    ip::IpRange,
+    programs, psl,
+    utils::{Input, Memfind},
+    Result,
+};
+
+/// `hust domain|ip [args]`: stored assets matching `args`.
+pub fn run(cfg: &Args, first: &OsString, args: &[OsString]) -> Result<()> {
+    let (path, v, filter) = (&cfg.path, cfg.verbosity, &cfg.filter);
+    let psl = match filter.apex {
+        Some(_) => psl::Psl::load(path)?,
+        None => psl::Psl::default(),
+    };
+    let asn_db = if filter.asn.is_empty() {
+        None
+    } else {
+        AsnDb::load(path)?
+    };
+    let store = if first == "ip" && filter.merged {
+        "cidr"
+    } else {
+        &first.to_string_lossy()
+    };
+
+    // Bad arguments are reported and the others still searched, an error if none is left
+    let ranges: Vec<_> = if first == "ip" {
+        let (ranges, errors): (Vec<_>, Vec<_>) = args
+            .iter()
+            .map(|arg| {
+                IpRange::from_str(&arg.to_string_lossy())
+                    .map(|range| (arg.to_string_lossy(), range))
+            })
+            .partition_result();
+        if ranges.is_empty() && !errors.is_empty() {
+            return Err(Error::Parse(errors.join("\n")));
+        }
+        for err in errors {
+            eprintln!("{}", Error::Parse(err));
+        }
+        ranges
+    } else {
+        Vec::new()
+    };
+
+    for program in programs::selected(cfg)? {
+        let Ok(e) = read_dir(program.path()) else {
+            continue;
+        };
+
+        for e in e.flatten().filter(|e| e.file_name() == store) {
+            let mmap = match Input::open(e.path()) {
+                Ok(mmap) => mmap,
+                Err(err) => {
+                    eprintln!("{err}");
+                    continue;
+                }
+            };
+            let mut matches = 0;
+            let tagged = match Tagged::load(&program.path(), filter, asn_db.as_ref()) {
+                Ok(tagged) => tagged,
+                Err(err) => {
+                    eprintln!("{err}");
+                    continue;
+                }
+            };
+
+            if first == "domain" {
+                for arg in mmap.find(args) {
+                    let domain = String::from_utf8_lossy(arg);
+                    if (filter.apex.is_some() && psl.apex(&domain) != filter.apex)
+                        || !tagged.matches_host(&domain)
+                    {
+                        continue;
+                    }
+                    matches += 1;
+                    if v {
+                        println!(
+                            "{} | {}",
+                            program.file_name().to_string_lossy(),
+                            String::from_utf8_lossy(arg)
+                        );
+                    } else {
+                        println!("{}", String::from_utf8_lossy(arg));
+                    }
+                }
+            } else {
+                // Search in CIDRs and IP ranges, filtered by tags
+
+                for line in mmap.find(&[]) {
+                    let line = String::from_utf8_lossy(line);
+                    let Ok(stored) = IpRange::from_str(&line) else {
+                        continue;
+                    };
+
+                    // Without arguments, only a tag filter lists IPs
+                    let arg = ranges
+                        .iter()
+                        .find(|(_, range)| filter.overlap.matches(&stored, range));
+                    if arg.is_none() && (!ranges.is_empty() || !filter.tagged()) {
+                        continue;
+                    }
+                    if !tagged.matches(&line, &stored) {
+                        continue;
+                    }
+
+                    matches += 1;
+                    if v {
+                        println!(
+                            "{} | {} | {}",
+                            program.file_name().to_string_lossy(),
+                            line,
+                            arg.map(|(arg, _)| arg.as_ref()).unwrap_or("*")
+                        );
+                    } else {
+                        println!("{}", line);
+                    }
+                }
+            }
+
+            if v {
+                eprintln!("{}: {matches} matches", e.path().to_string_lossy());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Tags of the IPs of a program, to filter its stored IPs or, with `-m`, CIDRs.
+pub struct Tagged<'a> {
+    filter: &'a Filter,
+    asn_db: Option<&'a AsnDb>,
+    asns: HashMap<IpCidr, String>,
+    providers: HashMap<IpCidr, String>,
+    /// Provider of resolved hosts
+    hosts: HashMap<String, String>,
+    /// Raw IPs sorted by address, to find the ones merged in a CIDR
+    ips: Vec<IpCidr>,
+}
+
+impl<'a> Tagged<'a> {
+    /// Read the stores of the program in `dir` needed by `filter`.
+    pub fn load(dir: &Path, filter: &'a Filter, asn_db: Option<&'a AsnDb>) -> Result<Self> {
+        let read = |name: &str, needed: bool| -> Result<Option<Input>> {
+            let path = dir.join(name);
+            Ok(if needed && path.is_file() {
+                Some(Input::open(path)?)
+            } else {
+                None
+            })
+        };
+
+        let mut ips =
+            read("ip", filter.merged && filter.tagged())?.map_or_else(Vec::new, |input| {
+                input
+                    .find(&[])
+                    .into_iter()
+                    .filter_map(|line| IpCidr::from_str(std::str::from_utf8(line).ok()?).ok())
+                    .collect()
+            });
+        ips.sort_by_key(|ip| (ip.first_as_ip_addr(), ip.last_as_ip_addr()));
+
+        let providers = read("provider", filter.provider.is_some() || filter.exclude_cdn)?;
+        Ok(Self {
+            filter,
+            asn_db,
+            asns: tags(&read("asn", !filter.asn.is_empty())?),
+            providers: tags(&providers),
+            hosts: tags(&providers),
+            ips,
+        })
+    }
+
+    /// Whether the stored `line` passes the tag filters, a CIDR of `-m` does if one of its IPs does.
+    pub fn matches(&self, line: &str, stored: &IpRange) -> bool {
+        if !self.filter.tagged() {
+            return true;
+        }
+        if !self.filter.merged {
+            return IpCidr::from_str(line).is_ok_and(|ip| self.passes(&ip));
+        }
+
+        let start = self
+            .ips
+            .partition_point(|ip| ip.first_as_ip_addr() < stored.first);
+        self.ips[start..]
+            .iter()
+            .take_while(|ip| ip.first_as_ip_addr() <= stored.last)
+            .any(|ip| stored.contains(&IpRange::from(ip)) && self.passes(ip))
+    }
+
+    /// Whether a stored domain passes the provider filters, tagged from the IPs it resolved to.
+    pub fn matches_host(&self, domain: &str) -> bool {
+        self.provider_passes(self.hosts.get(domain))
+    }
+
+    fn passes(&self, ip: &IpCidr) -> bool {
+        let filter = self.filter;
+        if !filter.asn.is_empty()
+            && !self
+                .asns
+                .get(ip)
+                .and_then(|asn| parse_asn(asn.split(" | ").next()?).ok())
+                // IPs stored without a tag are looked up
+                .or_else(|| Some(self.asn_db?.lookup(ip.first_as_ip_addr())?.0))
+                .is_some_and(|asn| filter.asn.contains(&asn))
+        {
+            return false;
+        }
+
+        self.provider_passes(self.providers.get(ip))
+    }
+
+    fn provider_passes(&self, provider: Option<&String>) -> bool {
+        let filter = self.filter;
+        !(filter
+            .provider
+            .as_ref()
+            .is_some_and(|p| provider != Some(p))
+            || (filter.exclude_cdn && provider.is_some_and(|p| cloud::is_cdn(p))))
+    }
+}
+
+/// Tags of a tag store, if it was read.
+fn tags<K: FromStr + Hash + Eq>(input: &Option<Input>) -> HashMap<K, String> {
+    input
+        .as_ref()
+        .map_or_else(HashMap::new, |input| database::tags(input.find(&[])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tests::test_dir;
+
+    #[test]
+    fn merged_tags() {
+        let dir = test_dir("tagged");
+        std::fs::write(dir.join("ip"), "1.0.0.1\n1.0.0.2\n1.0.0.3\n").unwrap();
+        std::fs::write(dir.join("provider"), "1.0.0.1 | cloudflare\n").unwrap();
+        let filter = |provider: &str, merged| Filter {
+            merged,
+            provider: Some(provider.into()),
+            ..Default::default()
+        };
+        let range = IpRange::from_str("1.0.0.0/30").unwrap();
+
+        let aws = filter("aws", true);
+        assert!(!Tagged::load(&dir, &aws, None)
+            .unwrap()
+            .matches("1.0.0.0/30", &range));
+        let cloudflare = filter("cloudflare", true);
+        let tagged = Tagged::load(&dir, &cloudflare, None).unwrap();
+        assert!(tagged.matches("1.0.0.0/30", &range));
+        assert!(!tagged.matches("1.0.0.4/30", &IpRange::from_str("1.0.0.4/30").unwrap()));
+
+        let cloudflare = filter("cloudflare", false);
+        let tagged = Tagged::load(&dir, &cloudflare, None).unwrap();
+        assert!(tagged.matches("1.0.0.1", &IpRange::from_str("1.0.0.1").unwrap()));
+        assert!(!tagged.matches("1.0.0.2", &IpRange::from_str("1.0.0.2").unwrap()));
+    }
+}
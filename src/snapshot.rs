@@ -0,0 +1,323 @@
+use chrono::{DateTime, Local};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    collections::BTreeSet,
+    ffi::{OsStr, OsString},
+    fs::{self, File},
+    io::Write,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    args::Args,
+    error::{Error, WithPath},
+    log, programs, status, utils, Result,
+};
+
+/// Snapshots are kept here: `<timestamp>.tar.gz` of the whole hunt path
+/// (its programs and `hust.log` with its segments) and `<program>/<timestamp>.tar.gz` of a program.
+pub const DIR: &str = ".snapshots";
+const EXT: &str = ".tar.gz";
+
+#[derive(Debug)]
+pub struct Snapshot {
+    /// `<timestamp>` or `<program>/<timestamp>`
+    pub id: String,
+    pub size: u64,
+    pub date: DateTime<Local>,
+}
+
+/// `hust snapshot`, `hust snapshots` and `hust restore <snapshot>`.
+pub fn run(cfg: &Args) -> Result<()> {
+    match cfg.args.first().map(|cmd| cmd.to_string_lossy()).as_deref() {
+        Some("snapshot") => {
+            if let Some(program) = &cfg.program {
+                programs::check_exists(&cfg.path, program)?;
+            }
+            let id = create(&cfg.path, cfg.program.as_deref())?;
+            println!("{id}");
+            Ok(())
+        }
+        Some("snapshots") => {
+            let mut stdout = std::io::stdout().lock();
+            for snapshot in list(&cfg.path, cfg.program.as_deref())? {
+                writeln!(
+                    stdout,
+                    "{:<40} {:>12}  {}",
+                    snapshot.id,
+                    status::human_size(snapshot.size),
+                    snapshot.date.format("%Y-%m-%d %H:%M:%S")
+                )?;
+            }
+            Ok(())
+        }
+        _ => {
+            let [_, id] = cfg.args.as_slice() else {
+                return Err(Error::Config("Usage: hust restore <snapshot>".into()));
+            };
+            let id = id.to_string_lossy();
+            file(&cfg.path, &id)?;
+            let program = program(&id);
+            let what = program.map_or("the whole hunt path".to_string(), |p| {
+                format!("program {p:?}")
+            });
+            if !cfg.yes && !programs::confirm(&format!("Replace {what} with snapshot {id}?"))? {
+                return Err(Error::Config("Not restored, confirm or use --yes".into()));
+            }
+
+            // Restoring can be rolled back too, unless the program is gone
+            match program {
+                Some(program) if !cfg.path.join(program).is_dir() => (),
+                program => {
+                    let current = create(&cfg.path, program.map(OsStr::new))?;
+                    eprintln!("Current state saved as snapshot {current}");
+                }
+            }
+            restore(&cfg.path, &id)
+        }
+    }
+}
+
+/// Save a compressed copy of `program`, or of the whole hunt path, and return its id.
+pub fn create(path: &Path, program: Option<&OsStr>) -> Result<String> {
+    let dir = match program {
+        Some(program) => path.join(DIR).join(program),
+        None => path.join(DIR),
+    };
+    fs::create_dir_all(&dir).with_path(&dir)?;
+
+    let stamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+    let name = (0..)
+        .map(|n| match n {
+            0 => stamp.clone(),
+            n => format!("{stamp}-{n}"),
+        })
+        .find(|name| !dir.join(format!("{name}{EXT}")).exists())
+        .unwrap_or(stamp);
+
+    // Written aside and renamed, so a failure doesn't leave a broken snapshot
+    let tmp = dir.join(format!(".{name}{EXT}"));
+    let file = File::create(&tmp).with_path(&tmp)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    match program {
+        Some(program) => builder
+            .append_dir_all(".", path.join(program))
+            .with_path(path.join(program))?,
+        None => {
+            for entry in hunt(path)? {
+                let name = entry.file_name().unwrap_or_default();
+                if entry.is_dir() {
+                    builder.append_dir_all(name, &entry)
+                } else {
+                    builder.append_path_with_name(&entry, name)
+                }
+                .with_path(&entry)?;
+            }
+        }
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .with_path(&tmp)?;
+
+    let file = dir.join(format!("{name}{EXT}"));
+    fs::rename(&tmp, &file).with_path(&file)?;
+
+    Ok(match program {
+        Some(program) => format!("{}/{name}", program.to_string_lossy()),
+        None => name,
+    })
+}
+
+/// Move the snapshots of program `from` to `to`, there may be none.
+pub fn rename(path: &Path, from: &OsStr, to: &OsStr) -> Result<()> {
+    let (from, to) = (path.join(DIR).join(from), path.join(DIR).join(to));
+    if !from.exists() {
+        return Ok(());
+    }
+    if to.exists() {
+        return Err(Error::Config(format!(
+            "{} already exists, move it before renaming",
+            to.to_string_lossy()
+        )));
+    }
+    fs::rename(&from, &to).with_path(&from)
+}
+
+/// What a snapshot of the whole hunt path holds: the programs, `hust.log` and its segments.
+fn hunt(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = utils::programs(path)?
+        .into_iter()
+        .map(|program| program.path())
+        .collect::<Vec<_>>();
+    let log = path.join("hust.log");
+    entries.extend(log::segments(&log)?);
+    if log.is_file() {
+        entries.push(log);
+    }
+    Ok(entries)
+}
+
+/// Snapshots of `program`, or all of them, oldest first.
+pub fn list(path: &Path, program: Option<&OsStr>) -> Result<Vec<Snapshot>> {
+    let dir = path.join(DIR);
+    let mut snapshots = Vec::new();
+    if !dir.is_dir() {
+        return Ok(snapshots);
+    }
+
+    let mut read = |dir: &Path, prefix: &str| -> Result<()> {
+        for entry in fs::read_dir(dir).with_path(dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (Some(stamp), Ok(meta)) = (name.strip_suffix(EXT), entry.metadata()) else {
+                continue;
+            };
+            if stamp.starts_with('.') {
+                continue;
+            }
+            snapshots.push(Snapshot {
+                id: format!("{prefix}{stamp}"),
+                size: meta.len(),
+                date: meta.modified().with_path(entry.path())?.into(),
+            });
+        }
+        Ok(())
+    };
+
+    match program {
+        Some(program) => {
+            let dir = dir.join(program);
+            if dir.is_dir() {
+                read(&dir, &format!("{}/", program.to_string_lossy()))?;
+            }
+        }
+        None => {
+            read(&dir, "")?;
+            for entry in fs::read_dir(&dir).with_path(&dir)?.flatten() {
+                if entry.path().is_dir() {
+                    read(
+                        &entry.path(),
+                        &format!("{}/", entry.file_name().to_string_lossy()),
+                    )?;
+                }
+            }
+        }
+    }
+
+    snapshots.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+    Ok(snapshots)
+}
+
+/// Program of a snapshot id, `None` for the whole hunt path.
+pub fn program(id: &str) -> Option<&str> {
+    id.split_once('/').map(|(program, _)| program)
+}
+
+/// Archive of snapshot `id`, error if there is none.
+pub fn file(path: &Path, id: &str) -> Result<PathBuf> {
+    let file = path.join(DIR).join(format!("{id}{EXT}"));
+    if id
+        .split('/')
+        .any(|part| part.is_empty() || part.starts_with('.'))
+        || !file.is_file()
+    {
+        return Err(Error::Config(format!(
+            "Snapshot {id:?} doesn't exist, see `hust snapshots`"
+        )));
+    }
+    Ok(file)
+}
+
+/// Extract snapshot `id` into `to`.
+pub fn unpack(path: &Path, id: &str, to: &Path) -> Result<()> {
+    let file = file(path, id)?;
+    fs::create_dir_all(to).with_path(to)?;
+    tar::Archive::new(GzDecoder::new(File::open(&file).with_path(&file)?))
+        .unpack(to)
+        .with_path(&file)
+}
+
+/// Top level names in the archive of a snapshot.
+fn roots(file: &Path) -> Result<BTreeSet<OsString>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(file).with_path(file)?));
+    let mut roots = BTreeSet::new();
+    for entry in archive.entries().with_path(file)? {
+        let entry = entry.with_path(file)?;
+        if let Some(Component::Normal(root)) = entry.path().with_path(file)?.components().next() {
+            roots.insert(root.to_os_string());
+        }
+    }
+    Ok(roots)
+}
+
+/// Replace the program, or what the snapshot holds of the hunt path, with snapshot `id`.
+///
+/// Nothing that isn't in the snapshot is removed.
+pub fn restore(path: &Path, id: &str) -> Result<()> {
+    let archive = file(path, id)?;
+    match program(id) {
+        Some(program) => {
+            let dir = path.join(program);
+            if dir.exists() {
+                fs::remove_dir_all(&dir).with_path(&dir)?;
+            }
+            unpack(path, id, &dir)
+        }
+        None => {
+            for root in roots(&archive)? {
+                let entry = path.join(&root);
+                if root == DIR || !entry.exists() {
+                    continue;
+                }
+                if entry.is_dir() {
+                    fs::remove_dir_all(&entry)
+                } else {
+                    fs::remove_file(&entry)
+                }
+                .with_path(&entry)?;
+            }
+            unpack(path, id, path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_and_restore() {
+        let path = crate::utils::tests::test_dir("snapshot");
+        fs::create_dir_all(path.join("x")).unwrap();
+        fs::write(path.join("x/domain"), "a.com").unwrap();
+        fs::write(path.join("hust.log"), "x | a.com | date").unwrap();
+        fs::write(path.join("notes.txt"), "mine").unwrap();
+
+        let program = create(&path, Some("x".as_ref())).unwrap();
+        let whole = create(&path, None).unwrap();
+        assert!(program.starts_with("x/"));
+        assert_eq!(list(&path, None).unwrap().len(), 2);
+        assert_eq!(list(&path, Some("x".as_ref())).unwrap()[0].id, program);
+
+        fs::write(path.join("x/domain"), "wrong").unwrap();
+        fs::write(path.join("x/ip"), "1.2.3.4").unwrap();
+        restore(&path, &program).unwrap();
+        assert_eq!(fs::read_to_string(path.join("x/domain")).unwrap(), "a.com");
+        assert!(!path.join("x/ip").exists());
+
+        // Only what the snapshot holds is replaced
+        fs::remove_file(path.join("hust.log")).unwrap();
+        fs::write(path.join("notes.txt"), "changed").unwrap();
+        fs::write(path.join("asn.tsv"), "added").unwrap();
+        restore(&path, &whole).unwrap();
+        assert!(path.join("hust.log").is_file());
+        assert_eq!(
+            fs::read_to_string(path.join("notes.txt")).unwrap(),
+            "changed"
+        );
+        assert!(path.join("asn.tsv").is_file());
+        assert_eq!(list(&path, None).unwrap().len(), 2);
+
+        assert!(restore(&path, "../x").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use chrono::{DateTime, Duration, Utc};
+use cidr_utils::{cidr::IpCidr, utils::IpCidrCombiner};
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    fs::{self, DirEntry},
+    io::Write,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    args::{self, Args},
+    ip::IpRange,
+    log,
+    psl::Psl,
+    utils::{self, Input, Memfind},
+    Result,
+};
+
+const BANNER: &str = "
+    ██░ ██  █    ██   ██████ ▄▄▄█████▓
+    ▓██░ ██▒ ██  ▓██▒▒██    ▒ ▓  ██▒ ▓▒
+    ▒██▀▀██░▓██  ▒██░░ ▓██▄   ▒ ▓██░ ▒░
+    ░▓█ ░██ ▓▓█  ░██░  ▒   ██▒░ ▓██▓ ░ 
+    ░▓█▒░██▓▒▒█████▓ ▒██████▒▒  ▒██▒ ░ 
+    ▒ ░░▒░▒░▒▓▒ ▒ ▒ ▒ ▒▓▒ ▒ ░  ▒ ░░   
+    ▒ ░▒░ ░░░▒░ ░ ░ ░ ░▒  ░ ░    ░    
+    ░  ░░ ░ ░░░ ░ ░ ░  ░  ░    ░      
+    ░  ░  ░   ░           ░            
+";
+pub fn run(cfg: &Args) -> Result<()> {
+    let psl = Psl::load(&cfg.path)?;
+    let new = new_assets(&cfg.log.read(&cfg.path, None)?, Utc::now())?;
+    let programs = utils::programs(&cfg.path)?
+        .iter()
+        .map(|program| ProgramStatus::collect(program, &psl, &new))
+        .collect_vec();
+    let config_file = args::get_config_file()?.0;
+
+    let mut stdout = std::io::stdout();
+    if cfg.json {
+        let json = serde_json::json!({
+            "hunt_path": cfg.path,
+            "config_file": config_file,
+            "webhooks": cfg.webhooks.iter().map(|w| w.to_string()).collect_vec(),
+            "programs": programs.iter().map(|p| p.json()).collect_vec(),
+        });
+        writeln!(stdout, "{json}")?;
+        return Ok(());
+    }
+
+    write!(
+        stdout,
+        "{}\n        Taste That PINK VENOM!    \n\nHunt Path: {}\n\nWebhooks: {}\n\nConfig file: {}",
+        BANNER,
+        &cfg.path.to_string_lossy(),
+        cfg.webhooks
+            .iter()
+            .join("             \n"),
+        config_file.to_string_lossy()
+    )?;
+
+    write!(stdout, "\n\nPrograms:")?;
+    for p in programs.iter() {
+        write!(
+            stdout,
+            "\n  {}: {} domains in {} apexes, {} IP ranges ({} IPv4 addresses{}), {} other, {} new in 24h, {} in 7d, {}",
+            p.name,
+            p.domains,
+            p.apexes.len(),
+            p.ip_ranges,
+            p.ipv4_addresses,
+            if p.ipv6_addresses > 0 {
+                format!(", {} IPv6", p.ipv6_addresses)
+            } else {
+                String::new()
+            },
+            p.other,
+            p.new_24h,
+            p.new_7d,
+            human_size(p.disk_usage)
+        )?;
+        if cfg.verbosity {
+            for (apex, count) in p
+                .apexes
+                .iter()
+                .sorted_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)))
+            {
+                write!(stdout, "\n    {apex} ({count})")?;
+            }
+        }
+    }
+    writeln!(stdout)?;
+
+    Ok(())
+}
+
+/// Statistics of a program shown by `status`.
+#[derive(Debug, Default)]
+pub struct ProgramStatus {
+    pub name: String,
+    pub domains: usize,
+    /// Domains per apex
+    pub apexes: HashMap<String, usize>,
+    /// Merged CIDRs
+    pub ip_ranges: usize,
+    pub ipv4_addresses: u128,
+    pub ipv6_addresses: u128,
+    pub other: usize,
+    pub new_24h: usize,
+    pub new_7d: usize,
+    /// Bytes
+    pub disk_usage: u64,
+}
+
+impl ProgramStatus {
+    pub fn collect(program: &DirEntry, psl: &Psl, new: &HashMap<String, (usize, usize)>) -> Self {
+        let dir = program.path();
+        let name = program.file_name().to_string_lossy().to_string();
+        let mut status = Self {
+            new_24h: new.get(&name).map(|n| n.0).unwrap_or_default(),
+            new_7d: new.get(&name).map(|n| n.1).unwrap_or_default(),
+            name,
+            ..Default::default()
+        };
+
+        if let Ok(domains) = Input::open(dir.join("domain")) {
+            for domain in domains.find(&[]) {
+                status.domains += 1;
+                if let Some(apex) = psl.apex(&String::from_utf8_lossy(domain)) {
+                    *status.apexes.entry(apex).or_default() += 1;
+                }
+            }
+        }
+
+        for range in ip_ranges(&dir) {
+            status.ip_ranges += 1;
+            match (range.first, range.last) {
+                (IpAddr::V4(first), IpAddr::V4(last)) => {
+                    status.ipv4_addresses += (u32::from(last) - u32::from(first)) as u128 + 1
+                }
+                (IpAddr::V6(first), IpAddr::V6(last)) => {
+                    status.ipv6_addresses = status
+                        .ipv6_addresses
+                        .saturating_add((u128::from(last) - u128::from(first)).saturating_add(1))
+                }
+                _ => (),
+            }
+        }
+
+        status.other = Input::open(dir.join("other"))
+            .map(|other| other.find(&[]).len())
+            .unwrap_or_default();
+
+        status.disk_usage = disk_usage(&dir);
+        status
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "domains": self.domains,
+            "apexes": self.apexes,
+            "ip_ranges": self.ip_ranges,
+            // Too big for JSON numbers
+            "ipv4_addresses": self.ipv4_addresses.to_string(),
+            "ipv6_addresses": self.ipv6_addresses.to_string(),
+            "other": self.other,
+            "new_24h": self.new_24h,
+            "new_7d": self.new_7d,
+            "disk_usage": self.disk_usage,
+        })
+    }
+}
+
+/// Merged IP ranges of the program in `dir`, merged here from `ip` for
+/// programs that weren't written since the `cidr` store exists.
+fn ip_ranges(dir: &Path) -> Vec<IpRange> {
+    let lines = |name| {
+        Input::open(dir.join(name)).map(|input| {
+            input
+                .find(&[])
+                .into_iter()
+                .map(|line| String::from_utf8_lossy(line).to_string())
+                .collect_vec()
+        })
+    };
+
+    if let Ok(cidrs) = lines("cidr") {
+        return cidrs
+            .iter()
+            .filter_map(|line| IpRange::from_str(line).ok())
+            .collect();
+    }
+    let mut merged = IpCidrCombiner::new();
+    for ip in lines("ip")
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|line| IpCidr::from_str(line).ok())
+    {
+        merged.push(ip);
+    }
+    merged
+        .get_ipv4_cidrs()
+        .iter()
+        .map(|cidr| IpRange::from(&IpCidr::V4(*cidr)))
+        .chain(
+            merged
+                .get_ipv6_cidrs()
+                .iter()
+                .map(|cidr| IpRange::from(&IpCidr::V6(*cidr))),
+        )
+        .collect()
+}
+
+/// Assets logged per program in the last 24 hours and 7 days.
+pub fn new_assets(logs: &[PathBuf], now: DateTime<Utc>) -> Result<HashMap<String, (usize, usize)>> {
+    let mut new = HashMap::new();
+    log::each(logs, |entry| {
+        let age = now.signed_duration_since(entry.date);
+        if !entry.reverted && age <= Duration::days(7) {
+            let count: &mut (usize, usize) = new.entry(entry.program.to_string()).or_default();
+            count.1 += 1;
+            if age <= Duration::hours(24) {
+                count.0 += 1;
+            }
+        }
+    })?;
+
+    Ok(new)
+}
+
+/// Total size of the files in `dir`, recursively.
+fn disk_usage(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some(if meta.is_dir() {
+                disk_usage(&e.path())
+            } else {
+                meta.len()
+            })
+        })
+        .sum()
+}
+
+/// `1536` → `1.5 KiB`
+pub fn human_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB", "GiB"] {
+        if size < 1024.0 {
+            return format!("{size:.1} {unit}");
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} TiB")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_in_windows() {
+        let log = utils::tests::test_dir("status").join("hust.log");
+        fs::write(
+            &log,
+            "x | a.com | Mon, 19 Oct 2026 07:00:00 +0000\n\
+             x | b.com | Fri, 16 Oct 2026 07:00:00 +0000\n\
+             x | c.com | Mon, 05 Oct 2026 07:00:00 +0000\n",
+        )
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        assert_eq!(new_assets(&[log], now).unwrap()["x"], (1, 2));
+        assert_eq!(human_size(1536), "1.5 KiB");
+    }
+
+    #[test]
+    fn ranges_without_cidr_store() {
+        let dir = utils::tests::test_dir("ranges");
+        fs::write(
+            dir.join("ip"),
+            "10.0.0.0
+10.0.0.1
+10.0.0.2/31
+::1
+",
+        )
+        .unwrap();
+        assert_eq!(
+            ip_ranges(&dir),
+            [
+                IpRange::from_str("10.0.0.0/30").unwrap(),
+                IpRange::from_str("::1").unwrap()
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use serde_json::Value;
+use std::{net::IpAddr, str::FromStr};
+
+/// How piped input is read by `insert`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// JSON lines are parsed, anything else is split into tokens
+    #[default]
+    Auto,
+    /// Every line must be JSON, others are rejected
+    Jsonl,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "jsonl" | "ndjson" | "json" => Ok(Self::Jsonl),
+            _ => Err(format!("{s:?} is not one of auto, jsonl")),
+        }
+    }
+}
+
+/// Assets found in a JSON line of subfinder, httpx, nuclei, amass, dnsx and alike.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    pub assets: Vec<String>,
+    /// Main host of the line, sources are tagged on it
+    pub host: Option<String>,
+    pub sources: Vec<String>,
+    /// Host of the line and the IPs it resolved to, from dnsx, httpx and amass
+    pub resolved: Option<(String, Vec<String>)>,
+}
+
+impl Record {
+    pub fn parse(line: &[u8]) -> Option<Self> {
+        let line = line.trim_ascii();
+        if !line.starts_with(b"{") {
+            return None;
+        }
+        let Value::Object(json) = serde_json::from_slice(line).ok()? else {
+            return None;
+        };
+
+        let mut record = Record::default();
+
+        // Hosts, IPs and URLs
+        for key in [
+            "host",
+            "name",
+            "input",
+            "domain",
+            "ip",
+            "host_ip",
+            "url",
+            "matched-at",
+        ] {
+            if let Some(Value::String(value)) = json.get(key) {
+                record.push(value);
+            }
+        }
+        // DNS answers
+        for key in ["a", "aaaa", "cname"] {
+            if let Some(Value::Array(values)) = json.get(key) {
+                values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .for_each(|v| record.push(v));
+            }
+        }
+        // amass
+        if let Some(Value::Array(addresses)) = json.get("addresses") {
+            addresses
+                .iter()
+                .filter_map(|a| a.get("ip")?.as_str())
+                .for_each(|ip| record.push(ip));
+        }
+
+        let ips = record
+            .assets
+            .iter()
+            .filter(|asset| IpAddr::from_str(asset).is_ok())
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some(host) = record
+            .assets
+            .iter()
+            .find(|asset| !asset.contains(':') && IpAddr::from_str(asset).is_err())
+            .filter(|_| !ips.is_empty())
+        {
+            record.resolved = Some((host.clone(), ips));
+        }
+
+        record.host = ["host", "name"]
+            .iter()
+            .filter_map(|key| json.get(*key)?.as_str())
+            .map(|host| host_of(host).unwrap_or(host).to_string())
+            .next();
+
+        if let (Some(host), Some(port)) = (&record.host, json.get("port")) {
+            let port = match port {
+                Value::String(port) => port.clone(),
+                Value::Number(port) => port.to_string(),
+                _ => String::new(),
+            };
+            if !port.is_empty() {
+                record.add(format!("{host}:{port}"));
+            }
+        }
+
+        match (json.get("sources"), json.get("source")) {
+            (Some(Value::Array(sources)), _) => record
+                .sources
+                .extend(sources.iter().filter_map(Value::as_str).map(String::from)),
+            (_, Some(Value::String(source))) => record.sources.push(source.clone()),
+            _ => (),
+        }
+
+        Some(record)
+    }
+
+    /// A URL goes to `other` and its host is extracted, `host:port` is cut to host.
+    fn push(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        if value.contains("://") {
+            self.add(value.to_string());
+        }
+        match host_of(value) {
+            Some(host) => self.add(host.to_string()),
+            None => self.add(value.to_string()),
+        }
+    }
+
+    fn add(&mut self, asset: String) {
+        if !self.assets.contains(&asset) {
+            self.assets.push(asset);
+        }
+    }
+}
+
+/// Host of a URL or of `host:port`.
+fn host_of(value: &str) -> Option<&str> {
+    if let Some(rest) = value.split_once("://").map(|(_, rest)| rest) {
+        let authority = rest.split(['/', '?', '#']).next()?;
+        let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+        return Some(strip_port(authority));
+    }
+    // Not an IPv6 address
+    if value.matches(':').count() == 1 {
+        return Some(strip_port(value));
+    }
+    None
+}
+
+fn strip_port(authority: &str) -> &str {
+    if authority.starts_with('[') {
+        return authority
+            .trim_start_matches('[')
+            .split(']')
+            .next()
+            .unwrap_or(authority);
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => authority,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subfinder() {
+        let r =
+            Record::parse(br#"{"host":"a.example.com","input":"example.com","source":"crtsh"}"#)
+                .unwrap();
+
+        assert_eq!(r.assets, ["a.example.com", "example.com"]);
+        assert_eq!(r.host.as_deref(), Some("a.example.com"));
+        assert_eq!(r.sources, ["crtsh"]);
+    }
+
+    #[test]
+    fn httpx() {
+        let r = Record::parse(
+            br#"{"url":"https://a.example.com:8443","host":"1.2.3.4","port":"8443","a":["1.2.3.4","1.2.3.5"],"title":"Hello world"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            r.assets,
+            [
+                "1.2.3.4",
+                "https://a.example.com:8443",
+                "a.example.com",
+                "1.2.3.5",
+                "1.2.3.4:8443"
+            ]
+        );
+    }
+
+    #[test]
+    fn amass_and_nuclei() {
+        let r = Record::parse(
+            br#"{"name":"a.example.com","domain":"example.com","addresses":[{"ip":"1.2.3.4","cidr":"1.2.3.0/24"}],"sources":["DNS","Crtsh"]}"#,
+        )
+        .unwrap();
+        assert_eq!(r.assets, ["a.example.com", "example.com", "1.2.3.4"]);
+        assert_eq!(
+            r.resolved,
+            Some(("a.example.com".into(), vec!["1.2.3.4".into()]))
+        );
+        assert_eq!(r.sources, ["DNS", "Crtsh"]);
+
+        let r = Record::parse(
+            br#"{"template-id":"x","host":"https://a.example.com","matched-at":"https://a.example.com/login","ip":"1.2.3.4"}"#,
+        )
+        .unwrap();
+        assert_eq!(r.host.as_deref(), Some("a.example.com"));
+        assert!(r
+            .assets
+            .contains(&"https://a.example.com/login".to_string()));
+
+        assert!(Record::parse(b"a.example.com").is_none());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, ffi::OsString, fmt::Write};
+
+use crate::{
+    args::Args,
+    programs,
+    psl::Psl,
+    utils::{Input, Memfind},
+    Result,
+};
+
+/// `hust tree -p program [apexes]`: the domains of a program per apex.
+pub fn run(cfg: &Args, apexes: &[OsString]) -> Result<()> {
+    cfg.program()?;
+    let store = programs::selected(cfg)?.remove(0).path().join("domain");
+    let psl = Psl::load(&cfg.path)?;
+
+    let mut tree = Tree::default();
+    // A new program has no store yet
+    if store.exists() {
+        for domain in Input::open(&store)?.find(&[]) {
+            let domain = String::from_utf8_lossy(domain).to_lowercase();
+            let Some(apex) = psl.apex(&domain) else {
+                continue;
+            };
+            if apexes.is_empty() || apexes.iter().any(|a| a == apex.as_str()) {
+                tree.insert(&apex, &domain);
+            }
+        }
+    }
+
+    print!("{}", tree.render(cfg.words.depth));
+    Ok(())
+}
+
+/// Domains as a label hierarchy, apex → sub → subsub.
+#[derive(Debug, Default)]
+pub struct Tree {
+    /// Domains in this subtree, including the node itself
+    count: usize,
+    children: BTreeMap<String, Tree>,
+}
+
+impl Tree {
+    /// Insert `domain` under its `apex`.
+    pub fn insert(&mut self, apex: &str, domain: &str) {
+        let sub = domain
+            .strip_suffix(apex)
+            .unwrap_or_default()
+            .trim_end_matches('.');
+
+        let mut node = self;
+        node.count += 1;
+        node = node.children.entry(apex.to_string()).or_default();
+        node.count += 1;
+        for label in sub.rsplit('.').filter(|l| !l.is_empty()) {
+            node = node.children.entry(label.to_string()).or_default();
+            node.count += 1;
+        }
+    }
+
+    /// Render every apex with at most `depth` levels below it.
+    pub fn render(&self, depth: Option<usize>) -> String {
+        let mut out = String::new();
+        for (apex, node) in &self.children {
+            let _ = writeln!(out, "{apex} ({})", node.count);
+            node.render_children("", depth, &mut out);
+        }
+        out
+    }
+
+    fn render_children(&self, prefix: &str, depth: Option<usize>, out: &mut String) {
+        if depth == Some(0) {
+            return;
+        }
+
+        let last = self.children.len().saturating_sub(1);
+        for (i, (label, node)) in self.children.iter().enumerate() {
+            let (branch, indent) = if i == last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let _ = writeln!(out, "{prefix}{branch}{label} ({})", node.count);
+            node.render_children(&format!("{prefix}{indent}"), depth.map(|d| d - 1), out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let mut tree = Tree::default();
+        for domain in [
+            "example.com",
+            "api.example.com",
+            "dev.api.example.com",
+            "www.example.com",
+        ] {
+            tree.insert("example.com", domain);
+        }
+
+        assert_eq!(
+            tree.render(None),
+            "example.com (4)\n├── api (2)\n│   └── dev (1)\n└── www (1)\n"
+        );
+        assert_eq!(
+            tree.render(Some(1)),
+            "example.com (4)\n├── api (2)\n└── www (1)\n"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use itertools::Itertools;
+use std::{ffi::OsString, io::Write};
+
+use crate::{args::Args, database::DataBase as db, error::Error, log, Result};
+
+/// Remove the assets of an insert from the stores, the last one by default.
+pub fn run(cfg: &Args) -> Result<()> {
+    let logs = cfg.log.read(&cfg.path, None)?;
+    let batch = match cfg.args.as_slice() {
+        [_] => log::last_batch(&logs)?
+            .ok_or_else(|| Error::Config("Nothing to undo in the log".into()))?,
+        [_, batch] => batch.to_string_lossy().to_string(),
+        _ => return Err(Error::Config("Usage: hust undo [batch]".into())),
+    };
+
+    let entries = log::batch(&logs, &batch)?;
+    if entries.is_empty() {
+        return Err(Error::Config(format!(
+            "Batch {batch:?} isn't in the log or is already undone"
+        )));
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    for (program, assets) in entries.into_iter().into_group_map() {
+        let program = OsString::from(program);
+        if !cfg.path.join(&program).is_dir() {
+            continue;
+        }
+        let mut db = db::init(&cfg.path, &program)?;
+        let removed = db.remove(&assets.iter().map(OsString::from).collect());
+        db.write()?;
+
+        if !cfg.quiet {
+            for asset in assets.iter() {
+                writeln!(stdout, "{asset}")?;
+            }
+        }
+        if cfg.verbosity {
+            eprintln!(
+                "{}: {removed} assets removed of batch {batch}",
+                program.to_string_lossy()
+            );
+        }
+    }
+    // Every copy of the lines, whatever is read
+    log::revert(&log::all(&cfg.path)?, &batch)
+}
@@ -1,16 +1,17 @@
-use crate::Result;
+use crate::{error::WithPath, Result};
 
 use itertools::Itertools;
 use memchr::memmem;
-use memmap2::Mmap;
+use memmap2::{Mmap, MmapOptions};
 use std::{
     ffi::OsString,
     fmt::Display,
-    fs::{File, OpenOptions},
-    io::{Read, Seek, Write},
+    fs::{self, DirEntry, File, OpenOptions},
+    io::{BufRead, Read, Seek, Write},
     ops::Deref,
     os::unix::ffi::OsStrExt,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 /// Trait to allow trimming ascii whitespace from a &[u8].
@@ -31,12 +32,50 @@ impl<T: Deref<Target = [u8]>> TrimAsciiWhitespace for T {
     }
 }
 
+/// Contents of a file.
+/// Non-empty regular files are mmapped, pipes and empty files are read into memory
+/// since mapping them fails.
+pub enum Input {
+    Map(Mmap),
+    Buf(Vec<u8>),
+}
+
+impl Input {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_path(path)?;
+        Self::from_file(&file).with_path(path)
+    }
+
+    pub fn from_file(mut file: &File) -> std::io::Result<Self> {
+        let meta = file.metadata()?;
+        if meta.is_file() && meta.len() > 0 {
+            Ok(Self::Map(unsafe { MmapOptions::new().map(file)? }))
+        } else {
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            Ok(Self::Buf(buf))
+        }
+    }
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Map(mmap) => mmap,
+            Input::Buf(buf) => buf,
+        }
+    }
+}
+
 pub trait Memfind<'a> {
     fn find(&'a self, needles: &[OsString]) -> Vec<&'a [u8]>;
 }
 
-impl<'a> Memfind<'a> for Mmap {
-    fn find(&'a self, needles: &[OsString]) -> Vec<&[u8]> {
+impl<'a> Memfind<'a> for Input {
+    fn find(&'a self, needles: &[OsString]) -> Vec<&'a [u8]> {
         self.split(|c| c == &b'\n')
             .filter(|l| !l.trim_ascii_whitespace().is_empty())
             .filter(|line| {
@@ -49,15 +88,99 @@ impl<'a> Memfind<'a> for Mmap {
     }
 }
 
+/// Lines of a reader, without the `\n`.
+pub fn lines(reader: impl BufRead) -> impl Iterator<Item = std::io::Result<Vec<u8>>> {
+    reader.split(b'\n')
+}
+
+/// How a line of piped input is cut into assets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Split {
+    /// Whitespace separated tokens
+    #[default]
+    Tokens,
+    /// The whole line is one asset
+    Lines,
+    Delimiter(String),
+}
+
+impl FromStr for Split {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(Self::Tokens),
+            "lines" => Ok(Self::Lines),
+            _ => Err(format!("{s:?} is not one of tokens, lines")),
+        }
+    }
+}
+
+impl Split {
+    pub fn split(&self, line: &[u8]) -> Vec<OsString> {
+        let line = line.trim_ascii_whitespace();
+        let parts = match self {
+            Split::Tokens => line.split(|c| c.is_ascii_whitespace()).collect_vec(),
+            Split::Lines => vec![line],
+            Split::Delimiter(delimiter) if delimiter.is_empty() => vec![line],
+            Split::Delimiter(delimiter) => {
+                let mut parts = Vec::new();
+                let mut rest = line;
+                while let Some(i) = memmem::find(rest, delimiter.as_bytes()) {
+                    parts.push(&rest[..i]);
+                    rest = &rest[i + delimiter.len()..];
+                }
+                parts.push(rest);
+                parts
+            }
+        };
+
+        parts
+            .into_iter()
+            .map(|part| part.trim_ascii())
+            .filter(|part| !part.is_empty())
+            .map(|part| unsafe { OsString::from_encoded_bytes_unchecked(part.to_vec()) })
+            .collect()
+    }
+
+    /// Assets of a reader, read line by line.
+    pub fn read<'a>(
+        &'a self,
+        reader: impl BufRead + 'a,
+    ) -> impl Iterator<Item = std::io::Result<OsString>> + 'a {
+        lines(reader).flat_map(|line| match line {
+            Ok(line) => self.split(&line).into_iter().map(Ok).collect_vec(),
+            Err(err) => vec![Err(err)],
+        })
+    }
+}
+
+/// Program directories in the hunt path, sorted by name.
+pub fn programs(path: &Path) -> Result<Vec<DirEntry>> {
+    Ok(fs::read_dir(path)
+        .with_path(path)?
+        .flatten()
+        .filter(|e| {
+            e.path().is_dir()
+                && e.file_name() != crate::cloud::DIR
+                && !e.file_name().as_bytes().starts_with(b".")
+        })
+        .sorted_by_key(|e| e.file_name())
+        .collect())
+}
+
 pub fn file_lines(path: impl AsRef<Path>) -> Result<(File, Vec<OsString>)> {
+    let path = path.as_ref();
     let mut file = OpenOptions::new()
         .read(true)
         .write(true)
         .create(true)
-        .open(path)?;
+        .truncate(false)
+        .open(path)
+        .with_path(path)?;
 
     let mut buf = Vec::new();
-    file.read_to_end(&mut buf)?;
+    file.read_to_end(&mut buf).with_path(path)?;
 
     Ok((
         file,
@@ -71,7 +194,7 @@ pub fn file_lines(path: impl AsRef<Path>) -> Result<(File, Vec<OsString>)> {
     ))
 }
 
-pub fn force_write<I, T>(file: &mut File, iter: I) -> Result<()>
+pub fn force_write<I, T>(file: &mut File, iter: I) -> std::io::Result<()>
 where
     I: IntoIterator<Item = T>,
     T: Display,
@@ -79,7 +202,14 @@ where
     file.set_len(0)?;
     file.seek(std::io::SeekFrom::Start(0))?;
 
-    Ok(file.write_all(iter.into_iter().join("\n").as_bytes())?)
+    file.write_all(iter.into_iter().join("\n").as_bytes())
+}
+
+/// Write `content` to a temporary file and move it over `path`.
+pub fn replace(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content).with_path(&tmp)?;
+    fs::rename(&tmp, path).with_path(path)
 }
 
 pub fn append(path: PathBuf, str: &str) -> Result<()> {
@@ -87,8 +217,8 @@ pub fn append(path: PathBuf, str: &str) -> Result<()> {
         .read(true)
         .append(true)
         .create(true)
-        .open(path)
-        .unwrap();
+        .open(&path)
+        .with_path(&path)?;
 
     let mut buf = [0; 1];
 
@@ -96,29 +226,74 @@ pub fn append(path: PathBuf, str: &str) -> Result<()> {
         && file.read_exact(&mut buf).is_ok()
         && buf[0] != b'\n'
     {
-        file.write_all(b"\n")?;
+        file.write_all(b"\n").with_path(&path)?;
+    }
+
+    file.write_all(str.as_bytes()).with_path(&path)?;
+    if !str.ends_with('\n') {
+        file.write_all(b"\n").with_path(&path)?;
     }
 
-    Ok(file.write_all(str.as_bytes())?)
+    Ok(())
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
 
+    /// Empty directory of a test, unique to it and to this run.
+    pub fn test_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hust.test.{name}.{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn tokens_skip_blanks() {
+        let tokens = Split::Tokens
+            .read(&b"a.com  b.com\n\n\tc\n"[..])
+            .map(|t| t.unwrap())
+            .collect_vec();
+
+        assert_eq!(tokens, ["a.com", "b.com", "c"]);
+    }
+
+    #[test]
+    fn split_lines_and_delimiter() {
+        let line = b" a.com [1.2.3.4] , Some Title ";
+
+        assert_eq!(Split::Lines.split(line), ["a.com [1.2.3.4] , Some Title"]);
+        assert_eq!(
+            Split::Delimiter(",".into()).split(line),
+            ["a.com [1.2.3.4]", "Some Title"]
+        );
+    }
+
+    #[test]
+    fn input_empty_file() {
+        let path = test_dir("empty").join("file");
+        File::create(&path).unwrap();
+
+        let input = Input::open(path).unwrap();
+
+        assert!(input.is_empty());
+        assert!(input.find(&[]).is_empty());
+    }
+
     #[test]
     fn append_1() {
-        let path = "/tmp/hust.test";
+        let path = test_dir("append_1").join("file");
         let mut file = OpenOptions::new()
             .truncate(true)
             .write(true)
             .create(true)
-            .open(path)
+            .open(&path)
             .unwrap();
 
         file.write_all(b"a").unwrap();
 
-        super::append(path.into(), "b").unwrap();
+        super::append(path.clone(), "b").unwrap();
 
         let file = std::fs::read_to_string(path).unwrap();
 
@@ -127,17 +302,17 @@ mod tests {
 
     #[test]
     fn append_2() {
-        let path = "/tmp/hust.test";
+        let path = test_dir("append_2").join("file");
         let mut file = OpenOptions::new()
             .truncate(true)
             .write(true)
             .create(true)
-            .open(path)
+            .open(&path)
             .unwrap();
 
         file.write_all(b"a\n").unwrap();
 
-        super::append(path.into(), "b").unwrap();
+        super::append(path.clone(), "b").unwrap();
 
         let file = std::fs::read_to_string(path).unwrap();
 
@@ -0,0 +1,247 @@
+use chrono::{DateTime, FixedOffset, SubsecRound, Utc};
+use itertools::Itertools;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::fs::{FileExt, MetadataExt},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{
+    args::Args, error::WithPath, hook, log, notification::send_notification, programs, Result,
+};
+
+/// How often the logs are polled.
+const POLL: Duration = Duration::from_secs(1);
+
+/// `hust watch`: print the assets logged from now on, optionally sent to the webhooks or a command.
+pub fn run(cfg: &Args) -> Result<()> {
+    if let Some(program) = &cfg.program {
+        programs::check_exists(&cfg.path, program)?;
+    }
+    let program = cfg.program.as_ref().map(|p| p.to_string_lossy());
+    // Dates are logged to the second
+    let start = Utc::now().trunc_subsecs(0);
+    let mut follows = cfg
+        .log
+        .read(&cfg.path, cfg.program.as_deref())?
+        .into_iter()
+        .map(|log| (log.clone(), Follow::new(log)))
+        .collect::<BTreeMap<_, _>>();
+
+    loop {
+        // Logs of the programs created meanwhile
+        for log in cfg.log.read(&cfg.path, cfg.program.as_deref())? {
+            follows
+                .entry(log)
+                .or_insert_with_key(|log| Follow::from_start(log.clone()));
+        }
+
+        let mut new = Vec::new();
+        for follow in follows.values_mut() {
+            for line in follow.lines()? {
+                let Some(entry) = log::Entry::parse(&line) else {
+                    continue;
+                };
+                // A renamed program's log appears with its old entries
+                if !entry.reverted
+                    && entry.date >= start
+                    && program.as_ref().is_none_or(|p| entry.program == *p)
+                {
+                    new.push((entry.program.to_string(), entry.asset.to_string()));
+                }
+            }
+        }
+
+        for (program, assets) in new.into_iter().into_group_map().into_iter().sorted() {
+            {
+                let mut stdout = std::io::stdout().lock();
+                for asset in assets.iter() {
+                    if cfg.verbosity {
+                        writeln!(stdout, "{program} | {asset}")?;
+                    } else {
+                        writeln!(stdout, "{asset}")?;
+                    }
+                }
+            }
+
+            // Failures are reported, watching goes on
+            if cfg.watch.notify {
+                let message = format!("## {program}\n{}", assets.join("\n"));
+                if let Err(err) = send_notification(&cfg.webhooks, message) {
+                    eprintln!("{err}");
+                }
+            }
+            if let Some(command) = &cfg.watch.exec {
+                let input = format!("{}\n", assets.join("\n"));
+                if let Err(err) = hook::run(command, &input, cfg.hook_timeout) {
+                    hook::report(&cfg.path, &program, "watch --exec", command, err);
+                }
+            }
+        }
+
+        std::thread::sleep(POLL);
+    }
+}
+
+/// Follows a log like `tail -F`, by polling.
+#[derive(Debug)]
+pub struct Follow {
+    path: PathBuf,
+    /// Kept open, so what is appended to a rotated log is still read
+    file: Option<File>,
+    inode: u64,
+    offset: u64,
+    /// Last line, until its newline is written
+    partial: Vec<u8>,
+}
+
+impl Follow {
+    /// Start at the end of `path`, which may not exist yet.
+    pub fn new(path: PathBuf) -> Self {
+        let mut follow = Self::from_start(path);
+        if let Ok(file) = File::open(&follow.path) {
+            follow.open(file, true);
+        }
+        follow
+    }
+
+    /// Start at the beginning of `path`, a log that appeared after watching started.
+    pub fn from_start(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            inode: 0,
+            offset: 0,
+            partial: Vec::new(),
+        }
+    }
+
+    fn open(&mut self, file: File, at_end: bool) {
+        let meta = file.metadata().ok();
+        self.inode = meta.as_ref().map(|meta| meta.ino()).unwrap_or_default();
+        self.offset = match (at_end, meta) {
+            (true, Some(meta)) => meta.len(),
+            _ => 0,
+        };
+        self.partial.clear();
+        self.file = Some(file);
+    }
+
+    /// Lines appended since the last call.
+    pub fn lines(&mut self) -> Result<Vec<String>> {
+        let mut lines = self.read()?;
+        let Ok(meta) = fs::metadata(&self.path) else {
+            return Ok(lines);
+        };
+
+        if meta.ino() != self.inode {
+            let Ok(file) = File::open(&self.path) else {
+                return Ok(lines);
+            };
+            // The rest of the old file is read above. A log rewritten by undo or rename
+            // starts with the same entry and its old lines aren't new, a rotated one starts anew.
+            let rewritten = self
+                .file
+                .as_ref()
+                .and_then(first)
+                .is_some_and(|old| first(&file) == Some(old));
+            self.open(file, rewritten);
+            lines.extend(self.read()?);
+        }
+        Ok(lines)
+    }
+
+    /// Lines appended to the open file since the last read.
+    fn read(&mut self) -> Result<Vec<String>> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(Vec::new());
+        };
+        let len = file.metadata().with_path(&self.path)?.len();
+        if len < self.offset {
+            // Truncated
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))
+            .with_path(&self.path)?;
+        let read = file
+            .take(len - self.offset)
+            .read_to_end(&mut self.partial)
+            .with_path(&self.path)?;
+        self.offset += read as u64;
+
+        let Some(end) = self.partial.iter().rposition(|c| *c == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.partial.split_off(end + 1);
+        let lines = String::from_utf8_lossy(&self.partial)
+            .lines()
+            .map(String::from)
+            .collect();
+        self.partial = rest;
+        Ok(lines)
+    }
+}
+
+/// Asset and date of the first entry of a log.
+fn first(file: &File) -> Option<(String, DateTime<FixedOffset>)> {
+    let mut line = Vec::new();
+    let mut buf = [0; 4096];
+    while !line.contains(&b'\n') {
+        let read = file.read_at(&mut buf, line.len() as u64).ok()?;
+        if read == 0 {
+            break;
+        }
+        line.extend_from_slice(&buf[..read]);
+    }
+    let line = String::from_utf8_lossy(line.split(|c| *c == b'\n').next()?).to_string();
+    let entry = log::Entry::parse(&line)?;
+    Some((entry.asset.into_owned(), entry.date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn follow() {
+        let dir = crate::utils::tests::test_dir("watch");
+        let path = dir.join("hust.log");
+        let line = |asset: &str| format!("x | {asset} | Mon, 19 Oct 2026 07:00:00 +0000");
+        fs::write(&path, format!("{}\n", line("old"))).unwrap();
+        let mut follow = Follow::new(path.clone());
+        assert!(follow.lines().unwrap().is_empty());
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{}\nx | b", line("a")).unwrap();
+        assert_eq!(follow.lines().unwrap(), [line("a")]);
+        writeln!(file, "{}", &line("b")[5..]).unwrap();
+        assert_eq!(follow.lines().unwrap(), [line("b")]);
+
+        // Truncated
+        fs::write(&path, "").unwrap();
+        assert!(follow.lines().unwrap().is_empty());
+        writeln!(file, "{}", line("c")).unwrap();
+        assert_eq!(follow.lines().unwrap(), [line("c")]);
+
+        // Rotated, the rest of the old file comes first
+        fs::rename(&path, dir.join("hust.log.1")).unwrap();
+        writeln!(file, "{}", line("late")).unwrap();
+        fs::write(&path, format!("{}\n", line("d"))).unwrap();
+        assert_eq!(follow.lines().unwrap(), [line("late"), line("d")]);
+
+        // Rewritten
+        crate::utils::replace(&path, format!("{} | reverted\n", line("d")).as_bytes()).unwrap();
+        assert!(follow.lines().unwrap().is_empty());
+        crate::utils::append(path.clone(), &line("e")).unwrap();
+        assert_eq!(follow.lines().unwrap(), [line("e")]);
+    }
+}
@@ -0,0 +1,145 @@
+use itertools::Itertools;
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+};
+
+use crate::{
+    args::Args,
+    programs,
+    psl::Psl,
+    utils::{Input, Memfind},
+    Result,
+};
+
+/// Number of top words permutated by default.
+const TOP: usize = 20;
+
+/// `hust words`: ranked labels of the domains of one or all programs, or their permutations.
+pub fn run(cfg: &Args) -> Result<()> {
+    let psl = Psl::load(&cfg.path)?;
+
+    let mut domains = HashSet::new();
+    for program in programs::selected(cfg)? {
+        if let Ok(input) = Input::open(program.path().join("domain")) {
+            domains.extend(
+                input
+                    .find(&[])
+                    .into_iter()
+                    .map(|d| String::from_utf8_lossy(d).to_lowercase()),
+            );
+        }
+    }
+
+    let subs = domains
+        .iter()
+        .filter_map(|domain| {
+            let apex = psl.apex(domain)?;
+            let sub = domain.strip_suffix(&apex)?.strip_suffix('.')?.to_string();
+            Some((sub, apex))
+        })
+        .sorted()
+        .collect_vec();
+    let ranked = rank(subs.iter().map(|(sub, _)| sub.as_str()));
+
+    let mut stdout = std::io::stdout().lock();
+    if cfg.words.permutations {
+        let words = ranked
+            .iter()
+            .take(cfg.words.top.unwrap_or(TOP))
+            .map(|(word, _)| *word)
+            .collect_vec();
+        let mut seen = HashSet::new();
+        for (sub, apex) in subs.iter() {
+            for candidate in permutations(sub, apex, &words, &domains) {
+                if seen.insert(candidate.clone()) {
+                    writeln!(stdout, "{candidate}")?;
+                }
+            }
+        }
+    } else {
+        for (word, count) in ranked.iter().take(cfg.words.top.unwrap_or(usize::MAX)) {
+            if cfg.verbosity {
+                writeln!(stdout, "{word} {count}")?;
+            } else {
+                writeln!(stdout, "{word}")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parts of a label split by `-` and digits, `dev-api2` → `dev`, `api`.
+pub fn fragments(label: &str) -> impl Iterator<Item = &str> {
+    label
+        .split(|c: char| c == '-' || c.is_ascii_digit())
+        .filter(|f| !f.is_empty())
+}
+
+/// Labels and their fragments of subdomains (apex removed), most frequent first.
+pub fn rank<'a>(subs: impl IntoIterator<Item = &'a str>) -> Vec<(&'a str, usize)> {
+    let mut counts = HashMap::new();
+    for sub in subs {
+        for label in sub.split('.').filter(|l| !l.is_empty()) {
+            *counts.entry(label).or_insert(0) += 1;
+            for fragment in fragments(label).filter(|f| *f != label) {
+                *counts.entry(fragment).or_insert(0) += 1;
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .sorted_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)))
+        .collect()
+}
+
+/// Candidates made of a known subdomain and `words`, e.g. `api.example.com` and `dev`
+/// give `dev-api`, `api-dev`, `dev.api`, and numbered `api1`..`api3`.
+pub fn permutations(sub: &str, apex: &str, words: &[&str], known: &HashSet<String>) -> Vec<String> {
+    let (label, rest) = match sub.split_once('.') {
+        Some((label, rest)) => (label, format!(".{rest}.{apex}")),
+        None => (sub, format!(".{apex}")),
+    };
+    let base = label.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    words
+        .iter()
+        .filter(|word| **word != label)
+        .flat_map(|word| {
+            [
+                format!("{word}-{label}{rest}"),
+                format!("{label}-{word}{rest}"),
+                format!("{word}.{label}{rest}"),
+            ]
+        })
+        .chain((1..=3).map(|n| format!("{base}{n}{rest}")))
+        .filter(|candidate| !known.contains(candidate))
+        .unique()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_fragments() {
+        let ranked = rank(["dev-api2", "api", "www.dev"]);
+
+        assert_eq!(ranked[0], ("api", 2));
+        assert_eq!(ranked[1], ("dev", 2));
+        assert!(ranked.contains(&("dev-api2", 1)));
+    }
+
+    #[test]
+    fn permutate() {
+        let known = HashSet::from(["api2.example.com".to_string()]);
+        let candidates = permutations("api", "example.com", &["dev"], &known);
+
+        assert!(candidates.contains(&"dev-api.example.com".to_string()));
+        assert!(candidates.contains(&"api1.example.com".to_string()));
+        assert!(!candidates.contains(&"api2.example.com".to_string()));
+    }
+}
@@ -1,3 +1,147 @@
-fn _test() {
-    assert!(true)
+use std::{fs, process::Command};
+
+fn hust() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_hust"))
+}
+
+/// Empty hunt path of a test, unique to it and to this run.
+fn hunt_dir(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("hust.first.{name}.{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+fn missing_program_is_config_error() {
+    let path = hunt_dir("input");
+    let input = path.join("input");
+    fs::write(&input, "example.com\n").unwrap();
+
+    let out = hust()
+        .arg("example.com")
+        .stdin(fs::File::open(&input).unwrap())
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Program (-p)"));
+
+    // Without arguments nor -p, stdin isn't read and the status is shown
+    let out = hust()
+        .arg("--path")
+        .arg(&path)
+        .stdin(fs::File::open(&input).unwrap())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Hunt Path"));
+}
+
+#[test]
+fn empty_stdin_and_empty_stores() {
+    let path = hunt_dir("empty");
+
+    let out = hust()
+        .args(["--create", "-p", "x", "--path"])
+        .arg(&path)
+        .stdin(std::process::Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+
+    fs::create_dir_all(path.join("y")).unwrap();
+    fs::write(path.join("y/domain"), "").unwrap();
+    let out = hust()
+        .args(["domain", "--path"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+    assert!(out.stdout.is_empty());
+}
+
+fn insert(path: &std::path::Path, flags: &[&str], input: &str) -> std::process::Output {
+    use std::io::Write;
+
+    let mut child = hust()
+        .args(["-n", "--create", "-p", "x", "--path"])
+        .arg(path)
+        .args(flags)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn insert_prints_only_new() {
+    let path = hunt_dir("new");
+
+    let out = insert(&path, &[], "a.example.com\nb.example.com\n");
+    assert_eq!(out.stdout, b"a.example.com\nb.example.com\n");
+
+    let out = insert(&path, &[], "b.example.com c.example.com\n");
+    assert_eq!(out.stdout, b"c.example.com\n");
+
+    let out = insert(&path, &["-q"], "d.example.com\n");
+    assert!(out.stdout.is_empty());
+}
+
+#[test]
+fn unknown_program_needs_create() {
+    let path = hunt_dir("create");
+
+    let out = hust()
+        .args(["-p", "typo", "a.example.com", "--path"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(!path.join("typo").exists());
+
+    let out = hust()
+        .args(["programs", "create", "typo", "--path"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+    let out = hust()
+        .args(["-p", "typo", "a.example.com", "--path"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+}
+
+#[test]
+fn undo_removes_last_batch() {
+    let path = hunt_dir("undo");
+
+    insert(&path, &[], "a.example.com\n");
+    insert(&path, &[], "b.example.com\n1.2.3.4\n");
+
+    let out = hust()
+        .args(["undo", "--path"])
+        .arg(&path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{:?}", out);
+    assert_eq!(out.stdout, b"b.example.com\n1.2.3.4\n");
+    assert_eq!(
+        fs::read_to_string(path.join("x/domain")).unwrap(),
+        "a.example.com"
+    );
+    assert_eq!(fs::read_to_string(path.join("x/ip")).unwrap(), "");
+
+    let out = insert(&path, &[], "b.example.com\n");
+    assert_eq!(out.stdout, b"b.example.com\n");
 }